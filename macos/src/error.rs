@@ -0,0 +1,19 @@
+#[must_use]
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("platform error: {0}")]
+    Platform(String),
+    #[error("pasteboard kept changing during read")]
+    ChangedDuringRead,
+    #[error("invalid utf16 string: {0}")]
+    InvalidUtf16(#[from] std::string::FromUtf16Error),
+    #[error("pasteboard accessed from a promise")]
+    Reentrancy,
+    #[error("type not available: {0}")]
+    Unavailable(String),
+    #[error("i/o error: {0}")]
+    Io(#[from] std::io::Error),
+    #[cfg(feature = "plist")]
+    #[error("invalid property list: {0}")]
+    InvalidPropertyList(#[from] plist::Error),
+}
@@ -11,17 +11,131 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+#![cfg(target_os = "macos")]
 
+mod error;
+mod options;
+mod promise;
+mod text;
+mod watcher;
+
+pub use error::Error;
+pub use options::ReadOptions;
+pub use promise::Promise;
+pub use watcher::{
+    Change, ChangeKind, ClipboardWatcher, Health, Interval, Subscription,
+};
+
+/// The representations of a pasteboard item, as pairs of type and data.
+pub type Item = Vec<(String, Vec<u8>)>;
+
+/// An encoded image format of the pasteboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// A PNG image, declared as `public.png`.
+    Png,
+    /// A TIFF image, declared as `public.tiff`.
+    Tiff,
+}
+
+impl ImageFormat {
+    /// Returns the pasteboard type of the [`ImageFormat`].
+    pub fn uti(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "public.png",
+            ImageFormat::Tiff => "public.tiff",
+        }
+    }
+}
+
+/// A write made by [`Clipboard::write_revocable`].
+pub struct Revocable {
+    clipboard: Clipboard,
+    change_count: isize,
+}
+
+impl Revocable {
+    /// Clears the pasteboard unless its `changeCount` has moved since the
+    /// write, returning whether it did.
+    ///
+    /// The previous contents are not restored.
+    pub fn revoke(self) -> Result<bool, Error> {
+        guard(|| {
+            if self.clipboard.change_count() != self.change_count {
+                return Ok(false);
+            }
+
+            self.clipboard.clear_contents();
+
+            Ok(true)
+        })
+    }
+}
+
+/// The name of a pasteboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasteboardName<'a> {
+    /// The general pasteboard, used for copy and paste.
+    General,
+    /// The pasteboard of the "Use Selection for Find" text.
+    Find,
+    /// The pasteboard of drag and drop operations.
+    Drag,
+    /// The pasteboard of copied font attributes.
+    Font,
+    /// The pasteboard of copied ruler attributes.
+    Ruler,
+    /// A pasteboard of its own, like an app-private one, by name.
+    ///
+    /// Applications sharing a name share the pasteboard.
+    Custom(&'a str),
+}
+
+/// How the system treats pasteboard reads by the application, as introduced
+/// by macOS 15.4.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessBehavior {
+    /// The system default applies.
+    Default,
+    /// The user is asked before the pasteboard is read.
+    Ask,
+    /// Reads are always allowed.
+    AlwaysAllow,
+    /// Reads are always denied.
+    AlwaysDeny,
+}
+
+use objc2::exception;
 use objc2::rc::Id;
 use objc2::runtime::{AnyClass, AnyObject, ProtocolObject};
-use objc2::{msg_send_id, ClassType};
-use objc2_app_kit::NSPasteboard;
-use objc2_foundation::{NSArray, NSString};
-use std::error::Error;
-use std::panic::{RefUnwindSafe, UnwindSafe};
+use objc2::{msg_send, msg_send_id, sel, ClassType};
+use objc2_app_kit::{
+    NSPasteboard, NSPasteboardItem, NSPasteboardNameDrag, NSPasteboardNameFind,
+    NSPasteboardNameFont, NSPasteboardNameGeneral, NSPasteboardNameRuler,
+};
+use objc2_foundation::{NSArray, NSData, NSString, NSURL};
+use std::any::Any;
+use std::collections::HashMap;
+use std::io::Write;
+use std::panic::{self, AssertUnwindSafe, RefUnwindSafe, UnwindSafe};
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Logs a format negotiation decision when the `log` feature is enabled.
+macro_rules! negotiation {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "log")]
+        log::debug!($($arg)*);
+    };
+}
+
+/// The maximum number of times [`Clipboard::read_consistent`] retries a read
+/// that raced with a pasteboard change.
+const MAX_CONSISTENT_READ_ATTEMPTS: usize = 5;
 
 pub struct Clipboard {
     pasteboard: Id<NSPasteboard>,
+    provider: Option<Id<promise::DataProvider>>,
 }
 
 unsafe impl Send for Clipboard {}
@@ -30,55 +144,845 @@ impl UnwindSafe for Clipboard {}
 impl RefUnwindSafe for Clipboard {}
 
 impl Clipboard {
-    pub fn new() -> Result<Clipboard, Box<dyn Error>> {
-        // Use `msg_send_id!` instead of `NSPasteboard::generalPasteboard()`
-        // in the off case that it will return NULL (even though it's
-        // documented not to).
-        let pasteboard: Option<Id<NSPasteboard>> =
-            unsafe { msg_send_id![NSPasteboard::class(), generalPasteboard] };
-        let pasteboard =
-            pasteboard.ok_or("NSPasteboard#generalPasteboard returned null")?;
-        Ok(Self { pasteboard })
-    }
-
-    pub fn read(&self) -> Result<String, Box<dyn Error>> {
+    pub fn new() -> Result<Clipboard, Error> {
+        guard(|| {
+            // Use `msg_send_id!` instead of
+            // `NSPasteboard::generalPasteboard()` in the off case that it
+            // will return NULL (even though it's documented not to).
+            let pasteboard: Option<Id<NSPasteboard>> = unsafe {
+                msg_send_id![NSPasteboard::class(), generalPasteboard]
+            };
+            let pasteboard = pasteboard.ok_or_else(|| {
+                platform("NSPasteboard#generalPasteboard returned null")
+            })?;
+            Ok(Self {
+                pasteboard,
+                provider: None,
+            })
+        })
+    }
+
+    /// Obtain a [`Clipboard`] backed by the find pasteboard.
+    ///
+    /// This is the pasteboard macOS uses to share the "Use Selection for
+    /// Find" text across applications.
+    pub fn find_pasteboard() -> Result<Clipboard, Error> {
+        Self::with_name(PasteboardName::Find)
+    }
+
+    /// Obtain a [`Clipboard`] backed by the pasteboard with the given
+    /// [`PasteboardName`], creating it if it does not exist.
+    pub fn with_name(name: PasteboardName<'_>) -> Result<Clipboard, Error> {
+        guard(|| {
+            let custom;
+            let name = match name {
+                PasteboardName::General => unsafe { NSPasteboardNameGeneral },
+                PasteboardName::Find => unsafe { NSPasteboardNameFind },
+                PasteboardName::Drag => unsafe { NSPasteboardNameDrag },
+                PasteboardName::Font => unsafe { NSPasteboardNameFont },
+                PasteboardName::Ruler => unsafe { NSPasteboardNameRuler },
+                PasteboardName::Custom(name) => {
+                    custom = NSString::from_str(name);
+                    &custom
+                }
+            };
+
+            let pasteboard = unsafe { NSPasteboard::pasteboardWithName(name) };
+
+            Ok(Self {
+                pasteboard,
+                provider: None,
+            })
+        })
+    }
+
+    pub fn read(&self) -> Result<String, Error> {
+        self.read_with_options(&ReadOptions::default())
+    }
+
+    /// Read the current [`Clipboard`] value using the given [`ReadOptions`].
+    pub fn read_with_options(
+        &self,
+        options: &ReadOptions,
+    ) -> Result<String, Error> {
+        guard(|| self.read_string(options))
+    }
+
+    /// Read the current [`Clipboard`] value along with the types declared on
+    /// the pasteboard, both from the same copy.
+    pub fn read_with_formats(&self) -> Result<(String, Vec<String>), Error> {
+        guard(|| {
+            self.read_consistent(|clipboard| {
+                let text = clipboard.read_string(&ReadOptions::default())?;
+
+                Ok((text, clipboard.types()))
+            })
+        })
+    }
+
+    fn read_string(&self, options: &ReadOptions) -> Result<String, Error> {
+        negotiation!("pasteboard offers {:?}", self.types());
+
+        self.read_object_string(options).or_else(|error| {
+            negotiation!("NSString read failed: {}", error);
+
+            // Some legacy applications only declare UTF-16 text types,
+            // which `NSString` does not pick up.
+            if let Ok(data) = self.data(text::UTF16_EXTERNAL_PLAIN_TEXT) {
+                negotiation!("read {}", text::UTF16_EXTERNAL_PLAIN_TEXT);
+
+                text::decode_utf16(data.bytes(), false)
+            } else if let Ok(data) = self.data(text::UTF16_PLAIN_TEXT) {
+                negotiation!("read {}", text::UTF16_PLAIN_TEXT);
+
+                text::decode_utf16(data.bytes(), cfg!(target_endian = "little"))
+            } else if options.file_urls_as_text {
+                negotiation!("no text types, reading file URLs");
+
+                self.read_file_paths(options)
+            } else {
+                Err(error)
+            }
+        })
+    }
+
+    fn read_object_string(
+        &self,
+        options: &ReadOptions,
+    ) -> Result<String, Error> {
+        // TODO: Use the NSPasteboard helper APIs (`stringForType`).
+        let strings = self.read_objects::<NSString>(options)?;
+        let string = strings.first().ok_or_else(|| {
+            platform("pasteboard#readObjectsForClasses:options: returned empty")
+        })?;
+
+        negotiation!("read NSString");
+
+        Ok(string.to_string())
+    }
+
+    /// Read the paths of the file URLs on the pasteboard, as Finder copies
+    /// them.
+    pub fn read_files(&self) -> Result<Vec<PathBuf>, Error> {
+        guard(|| {
+            let options = ReadOptions {
+                file_urls_only: true,
+                ..ReadOptions::default()
+            };
+
+            Ok(self
+                .read_objects::<NSURL>(&options)?
+                .iter()
+                .filter_map(|url| unsafe { url.path() })
+                .map(|path| PathBuf::from(path.to_string()))
+                .collect())
+        })
+    }
+
+    /// Write the given paths to the pasteboard as file URLs, so Finder can
+    /// paste the files.
+    pub fn write_files(&mut self, paths: &[PathBuf]) -> Result<(), Error> {
+        guard(|| {
+            let urls = paths
+                .iter()
+                .map(|path| {
+                    let path = path.to_str().ok_or_else(|| {
+                        Error::Platform(format!(
+                            "path is not valid UTF-8: {}",
+                            path.display()
+                        ))
+                    })?;
+
+                    Ok(ProtocolObject::from_id(unsafe {
+                        NSURL::fileURLWithPath(&NSString::from_str(path))
+                    }))
+                })
+                .collect::<Result<_, Error>>()?;
+
+            self.clear_contents();
+
+            let success = unsafe {
+                self.pasteboard.writeObjects(&NSArray::from_vec(urls))
+            };
+
+            if success {
+                Ok(())
+            } else {
+                Err(platform("NSPasteboard#writeObjects: returned false"))
+            }
+        })
+    }
+
+    /// Reads the paths of the file URLs on the pasteboard, escaped and
+    /// separated the way Finder pastes them into Terminal.
+    fn read_file_paths(&self, options: &ReadOptions) -> Result<String, Error> {
+        let options = ReadOptions {
+            file_urls_only: true,
+            ..options.clone()
+        };
+
+        let paths: Vec<String> = self
+            .read_objects::<NSURL>(&options)?
+            .iter()
+            .filter_map(|url| unsafe { url.path() })
+            .map(|path| text::shell_escape(&path.to_string()))
+            .collect();
+
+        if paths.is_empty() {
+            return Err(platform(
+                "pasteboard#readObjectsForClasses:options: returned empty",
+            ));
+        }
+
+        Ok(paths.join(" "))
+    }
+
+    fn read_objects<T: ClassType>(
+        &self,
+        options: &ReadOptions,
+    ) -> Result<Vec<Id<T>>, Error> {
         // The NSPasteboard API is a bit weird, it requires you to pass
         // classes as objects, which `objc2_foundation::NSArray` was not really
         // made for - so we convert the class to an `AnyObject` type instead.
-        //
-        // TODO: Use the NSPasteboard helper APIs (`stringForType`).
-        let string_class = {
-            let cls: *const AnyClass = NSString::class();
+        let class = {
+            let cls: *const AnyClass = T::class();
             let cls = cls as *mut AnyObject;
             unsafe { Id::retain(cls).unwrap() }
         };
-        let classes = NSArray::from_vec(vec![string_class]);
-        let string_array = unsafe {
+        let classes = NSArray::from_vec(vec![class]);
+        let options = options.to_dictionary();
+        let objects = unsafe {
             self.pasteboard
-                .readObjectsForClasses_options(&classes, None)
+                .readObjectsForClasses_options(&classes, options.as_deref())
         }
-        .ok_or("pasteboard#readObjectsForClasses:options: returned null")?;
+        .ok_or_else(|| {
+            platform("pasteboard#readObjectsForClasses:options: returned null")
+        })?;
+
+        // And this part is weird as well, since we now have to convert the
+        // objects into `T`, which we know them to be since that's what we
+        // told `readObjectsForClasses:options:`.
+        Ok(objects
+            .iter()
+            .map(|obj| {
+                let obj: *const AnyObject = obj;
+                unsafe { Id::retain(obj as *mut T) }.unwrap()
+            })
+            .collect())
+    }
+
+    /// Run `read` against the pasteboard, retrying whenever its contents
+    /// change mid-read.
+    ///
+    /// The pasteboard `changeCount` is recorded before and after `read`
+    /// runs, so multiple formats can be fetched without mixing items from
+    /// two different copies.
+    pub fn read_consistent<T>(
+        &self,
+        mut read: impl FnMut(&Self) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        for _ in 0..MAX_CONSISTENT_READ_ATTEMPTS {
+            let change_count = self.change_count();
+            let result = read(self);
+
+            if self.change_count() == change_count {
+                return result;
+            }
+        }
+
+        Err(Error::ChangedDuringRead)
+    }
+
+    /// Compute a fingerprint of the pasteboard data of the given `formats`.
+    ///
+    /// The data of each format is hashed straight from the `NSData` the
+    /// pasteboard returns, without another copy into a Rust buffer, and the
+    /// result is stable across processes and machines. Missing formats are
+    /// part of the fingerprint as well.
+    pub fn content_hash(&self, formats: &[&str]) -> Result<u64, Error> {
+        guard(|| {
+            self.read_consistent(|clipboard| {
+                let mut hash = FNV_OFFSET_BASIS;
+
+                for format in formats {
+                    let data = clipboard.data(format).ok();
+
+                    hash = fnv1a(hash, &(format.len() as u64).to_le_bytes());
+                    hash = fnv1a(hash, format.as_bytes());
+
+                    hash = match data {
+                        Some(data) => {
+                            let hash = fnv1a(hash, &[1]);
+                            let hash =
+                                fnv1a(hash, &(data.len() as u64).to_le_bytes());
+
+                            fnv1a(hash, data.bytes())
+                        }
+                        None => fnv1a(hash, &[0]),
+                    };
+                }
 
-        let obj: *const AnyObject = string_array.first().ok_or(
-            "pasteboard#readObjectsForClasses:options: returned empty",
-        )?;
-        // And this part is weird as well, since we now have to convert the object
-        // into an NSString, which we know it to be since that's what we told
-        // `readObjectsForClasses:options:`.
-        let obj: *mut NSString = obj as _;
-        Ok(unsafe { Id::retain(obj) }.unwrap().to_string())
+                Ok(hash)
+            })
+        })
     }
 
-    pub fn write(&mut self, data: String) -> Result<(), Box<dyn Error>> {
+    /// Write `data` to the pasteboard, declaring it only as the given text
+    /// `types`.
+    ///
+    /// UTF-16 types are encoded accordingly, while any other type is set
+    /// as a string.
+    pub fn write_text_types(
+        &mut self,
+        data: String,
+        types: &[&str],
+    ) -> Result<(), Error> {
+        guard(|| {
+            let declared = types.iter().map(|type_| NSString::from_str(type_));
+            let count = unsafe {
+                self.pasteboard.declareTypes_owner(
+                    &NSArray::from_vec(declared.collect()),
+                    None,
+                )
+            };
+            self.record_write(count);
+
+            for type_ in types {
+                let pasteboard_type = NSString::from_str(type_);
+
+                let success = match *type_ {
+                    text::UTF16_EXTERNAL_PLAIN_TEXT
+                    | text::UTF16_PLAIN_TEXT => {
+                        let bytes = text::encode_utf16(&data, type_);
+
+                        unsafe {
+                            self.pasteboard.setData_forType(
+                                Some(&NSData::with_bytes(&bytes)),
+                                &pasteboard_type,
+                            )
+                        }
+                    }
+                    _ => unsafe {
+                        self.pasteboard.setString_forType(
+                            &NSString::from_str(&data),
+                            &pasteboard_type,
+                        )
+                    },
+                };
+
+                if !success {
+                    return Err(Error::Platform(format!(
+                        "NSPasteboard could not set {}",
+                        type_
+                    )));
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Read the encoded image on the pasteboard, preferring PNG over TIFF.
+    pub fn read_image(&self) -> Result<(ImageFormat, Vec<u8>), Error> {
+        guard(|| {
+            for format in [ImageFormat::Png, ImageFormat::Tiff] {
+                if let Ok(data) = self.data(format.uti()) {
+                    negotiation!("read {}", format.uti());
+
+                    return Ok((format, data.bytes().to_vec()));
+                }
+            }
+
+            Err(Error::Unavailable(ImageFormat::Png.uti().to_owned()))
+        })
+    }
+
+    /// Write an encoded image to the pasteboard, declaring only its
+    /// [`ImageFormat`].
+    pub fn write_image(
+        &mut self,
+        format: ImageFormat,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        self.write_data(&[(format.uti(), data)])
+    }
+
+    /// Write each pair of type identifier and data to the pasteboard,
+    /// declaring only those types.
+    pub fn write_data(&mut self, items: &[(&str, &[u8])]) -> Result<(), Error> {
+        guard(|| {
+            let declared =
+                items.iter().map(|(type_, _)| NSString::from_str(type_));
+            let count = unsafe {
+                self.pasteboard.declareTypes_owner(
+                    &NSArray::from_vec(declared.collect()),
+                    None,
+                )
+            };
+            self.record_write(count);
+
+            for (type_, data) in items {
+                let success = unsafe {
+                    self.pasteboard.setData_forType(
+                        Some(&NSData::with_bytes(data)),
+                        &NSString::from_str(type_),
+                    )
+                };
+
+                if !success {
+                    return Err(Error::Platform(format!(
+                        "NSPasteboard could not set {}",
+                        type_
+                    )));
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Reads every item on the pasteboard, each as pairs of type and data.
+    ///
+    /// Types whose data cannot be read are left out.
+    pub fn read_items(&self) -> Result<Vec<Item>, Error> {
+        guard(|| {
+            let items = unsafe { self.pasteboard.pasteboardItems() }
+                .ok_or_else(|| platform("NSPasteboard#pasteboardItems: nil"))?;
+
+            Ok(items
+                .iter()
+                .map(|item| {
+                    unsafe { item.types() }
+                        .iter()
+                        .filter_map(|type_| {
+                            let data = unsafe { item.dataForType(type_) }?;
+
+                            Some((type_.to_string(), data.bytes().to_vec()))
+                        })
+                        .collect()
+                })
+                .collect())
+        })
+    }
+
+    /// Writes each item to the pasteboard, as pairs of type and data,
+    /// replacing its contents.
+    pub fn write_items(
+        &mut self,
+        items: &[Vec<(&str, &[u8])>],
+    ) -> Result<(), Error> {
+        guard(|| {
+            let items = items
+                .iter()
+                .map(|item| Ok(ProtocolObject::from_id(pasteboard_item(item)?)))
+                .collect::<Result<_, Error>>()?;
+
+            self.clear_contents();
+
+            let success = unsafe {
+                self.pasteboard.writeObjects(&NSArray::from_vec(items))
+            };
+
+            if success {
+                Ok(())
+            } else {
+                Err(platform("NSPasteboard#writeObjects: returned false"))
+            }
+        })
+    }
+
+    /// Writes the given types and data to the pasteboard, along with types
+    /// whose data is only produced when a paste requests them.
+    ///
+    /// Each [`Promise`] runs on the thread running the main run loop, at
+    /// most once per paste, for as long as the pasteboard keeps the written
+    /// item. Any [`Clipboard`] operation performed by the [`Promise`] fails
+    /// with [`Error::Reentrancy`].
+    pub fn write_promised(
+        &mut self,
+        items: &[(&str, &[u8])],
+        promises: HashMap<String, Promise>,
+    ) -> Result<(), Error> {
+        guard(|| {
+            let item = pasteboard_item(items)?;
+            let provider = promise::DataProvider::new(promises);
+            let success = unsafe {
+                item.setDataProvider_forTypes(
+                    ProtocolObject::from_ref(&*provider),
+                    &NSArray::from_vec(provider.types()),
+                )
+            };
+
+            if !success {
+                return Err(platform(
+                    "NSPasteboardItem#setDataProvider:forTypes: returned false",
+                ));
+            }
+
+            self.clear_contents();
+
+            let success = unsafe {
+                self.pasteboard.writeObjects(&NSArray::from_vec(vec![
+                    ProtocolObject::from_id(item),
+                ]))
+            };
+
+            // The provider is kept alive until the next promised write.
+            self.provider = Some(provider);
+
+            if success {
+                Ok(())
+            } else {
+                Err(platform("NSPasteboard#writeObjects: returned false"))
+            }
+        })
+    }
+
+    /// Writes text to the pasteboard, returning a [`Revocable`] that can
+    /// clear it later unless its contents have changed since.
+    pub fn write_revocable(
+        &mut self,
+        data: String,
+    ) -> Result<Revocable, Error> {
+        guard(|| {
+            self.write_string(&data)?;
+
+            Ok(Revocable {
+                clipboard: Clipboard {
+                    pasteboard: self.pasteboard.clone(),
+                    provider: None,
+                },
+                change_count: self.change_count(),
+            })
+        })
+    }
+
+    /// Removes every item from the pasteboard, without writing anything.
+    pub fn clear(&mut self) -> Result<(), Error> {
+        guard(|| {
+            self.clear_contents();
+
+            Ok(())
+        })
+    }
+
+    /// Returns the size in bytes of the data stored on the pasteboard for
+    /// `format`.
+    ///
+    /// The pasteboard has no way to tell the size alone, so the data is
+    /// still transferred as an `NSData`, but it is not copied into a Rust
+    /// buffer.
+    pub fn data_size(&self, format: &str) -> Result<usize, Error> {
+        guard(|| Ok(self.data(format)?.len()))
+    }
+
+    /// Returns the types declared on the pasteboard, in order of preference.
+    pub fn declared_types(&self) -> Result<Vec<String>, Error> {
+        guard(|| Ok(self.types()))
+    }
+
+    /// Returns whether the pasteboard declares each of the given `types`,
+    /// querying its types once.
+    pub fn available_types(&self, types: &[&str]) -> Result<Vec<bool>, Error> {
+        guard(|| {
+            let declared = self.types();
+
+            Ok(types
+                .iter()
+                .map(|&type_| declared.iter().any(|declared| declared == type_))
+                .collect())
+        })
+    }
+
+    /// Read the data stored on the pasteboard for `format`.
+    pub fn read_data(&self, format: &str) -> Result<Vec<u8>, Error> {
+        guard(|| Ok(self.data(format)?.bytes().to_vec()))
+    }
+
+    /// Read the data of every type declared on the pasteboard.
+    ///
+    /// Types whose data cannot be provided are left out.
+    pub fn read_all(&self) -> Result<HashMap<String, Vec<u8>>, Error> {
+        guard(|| {
+            Ok(self
+                .types()
+                .into_iter()
+                .filter_map(|type_| {
+                    let data = self.data(&type_).ok()?.bytes().to_vec();
+
+                    Some((type_, data))
+                })
+                .collect())
+        })
+    }
+
+    /// Write the data stored on the pasteboard for `format` to `writer`,
+    /// straight from the `NSData` the pasteboard returns, without another
+    /// copy into a Rust buffer.
+    ///
+    /// Returns the number of bytes written.
+    pub fn write_data_to(
+        &self,
+        format: &str,
+        writer: &mut dyn Write,
+    ) -> Result<u64, Error> {
+        guard(|| {
+            let data = self.data(format)?;
+            writer.write_all(data.bytes())?;
+
+            Ok(data.len() as u64)
+        })
+    }
+
+    /// Read the property list stored on the pasteboard for `format`.
+    #[cfg(feature = "plist")]
+    pub fn read_plist(&self, format: &str) -> Result<plist::Value, Error> {
+        guard(|| {
+            let data = self.data(format)?;
+
+            Ok(plist::Value::from_reader(std::io::Cursor::new(
+                data.bytes(),
+            ))?)
+        })
+    }
+
+    /// Write a property list to the pasteboard for `format`.
+    #[cfg(feature = "plist")]
+    pub fn write_plist(
+        &mut self,
+        format: &str,
+        value: &plist::Value,
+    ) -> Result<(), Error> {
+        guard(|| {
+            let mut data = Vec::new();
+            value.to_writer_xml(&mut data)?;
+
+            self.set_data(format, &data)
+        })
+    }
+
+    /// Returns the types declared on the pasteboard, in order of preference.
+    fn types(&self) -> Vec<String> {
+        unsafe { self.pasteboard.types() }
+            .map(|types| types.iter().map(|type_| type_.to_string()).collect())
+            .unwrap_or_default()
+    }
+
+    fn data(&self, format: &str) -> Result<Id<NSData>, Error> {
+        unsafe { self.pasteboard.dataForType(&NSString::from_str(format)) }
+            .ok_or_else(|| Error::Unavailable(format.to_owned()))
+    }
+
+    #[cfg(feature = "plist")]
+    fn set_data(&self, format: &str, data: &[u8]) -> Result<(), Error> {
+        self.clear_contents();
+
+        let success = unsafe {
+            self.pasteboard.setData_forType(
+                Some(&NSData::with_bytes(data)),
+                &NSString::from_str(format),
+            )
+        };
+
+        if success {
+            Ok(())
+        } else {
+            Err(platform("NSPasteboard#setData:forType: returned false"))
+        }
+    }
+
+    /// Returns the [`AccessBehavior`] of the pasteboard, or `None` if the
+    /// system predates pasteboard privacy settings.
+    pub fn access_behavior(&self) -> Result<Option<AccessBehavior>, Error> {
+        guard(|| {
+            let pasteboard: &NSPasteboard = &self.pasteboard;
+            let is_available: bool = unsafe {
+                msg_send![pasteboard, respondsToSelector: sel!(accessBehavior)]
+            };
+
+            if !is_available {
+                return Ok(None);
+            }
+
+            let behavior: isize =
+                unsafe { msg_send![pasteboard, accessBehavior] };
+
+            Ok(Some(match behavior {
+                1 => AccessBehavior::Ask,
+                2 => AccessBehavior::AlwaysAllow,
+                3 => AccessBehavior::AlwaysDeny,
+                _ => AccessBehavior::Default,
+            }))
+        })
+    }
+
+    /// Returns the change count of the pasteboard, which increases every
+    /// time its contents change.
+    pub fn change_count(&self) -> isize {
+        unsafe { self.pasteboard.changeCount() }
+    }
+
+    /// Returns whether the current contents of the pasteboard were written
+    /// by this process, through any [`Clipboard`] of the same pasteboard.
+    ///
+    /// This compares the `changeCount` of the pasteboard with the one
+    /// recorded by the last write of this process.
+    pub fn owns_contents(&self) -> bool {
+        let name = unsafe { self.pasteboard.name() }.to_string();
+        let count = self.change_count();
+
+        written().iter().any(|(written, written_count)| {
+            *written == name && *written_count == count
+        })
+    }
+
+    pub fn write(&mut self, data: String) -> Result<(), Error> {
+        guard(|| self.write_string(&data))
+    }
+
+    /// Clears the pasteboard before a write, recording the write.
+    fn clear_contents(&self) {
+        let count = unsafe { self.pasteboard.clearContents() };
+
+        self.record_write(count);
+    }
+
+    /// Records that this process changed the pasteboard to `count`.
+    fn record_write(&self, count: isize) {
+        let name = unsafe { self.pasteboard.name() }.to_string();
+        let mut written = written();
+
+        match written.iter_mut().find(|(written, _)| *written == name) {
+            Some((_, written_count)) => *written_count = count,
+            None => written.push((name, count)),
+        }
+    }
+
+    fn write_string(&self, data: &str) -> Result<(), Error> {
         let string_array = NSArray::from_vec(vec![ProtocolObject::from_id(
-            NSString::from_str(&data),
+            NSString::from_str(data),
         )]);
-        unsafe { self.pasteboard.clearContents() };
+        self.clear_contents();
         let success = unsafe { self.pasteboard.writeObjects(&string_array) };
         if success {
             Ok(())
         } else {
-            Err("NSPasteboard#writeObjects: returned false".into())
+            Err(platform("NSPasteboard#writeObjects: returned false"))
+        }
+    }
+}
+
+/// The `changeCount` of each pasteboard, by name, after its last write by
+/// this process.
+static WRITTEN: Mutex<Vec<(String, isize)>> = Mutex::new(Vec::new());
+
+fn written() -> MutexGuard<'static, Vec<(String, isize)>> {
+    WRITTEN.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Feeds `bytes` into a 64-bit FNV-1a `hash`.
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Creates a pasteboard item holding each pair of type and data.
+fn pasteboard_item(
+    items: &[(&str, &[u8])],
+) -> Result<Id<NSPasteboardItem>, Error> {
+    let item = unsafe { NSPasteboardItem::new() };
+
+    for (type_, data) in items {
+        let success = unsafe {
+            item.setData_forType(
+                &NSData::with_bytes(data),
+                &NSString::from_str(type_),
+            )
+        };
+
+        if !success {
+            return Err(Error::Platform(format!(
+                "NSPasteboardItem could not set {}",
+                type_
+            )));
+        }
+    }
+
+    Ok(item)
+}
+
+fn platform(message: &str) -> Error {
+    Error::Platform(message.to_owned())
+}
+
+/// Runs `f` at the API boundary, turning Objective-C exceptions and panics
+/// into [`Error::Platform`] instead of unwinding into the host application.
+fn guard<T>(f: impl FnOnce() -> Result<T, Error>) -> Result<T, Error> {
+    check_thread();
+    promise::check_reentrancy()?;
+
+    let result = unsafe {
+        exception::catch(AssertUnwindSafe(|| {
+            panic::catch_unwind(AssertUnwindSafe(f))
+        }))
+    };
+
+    match result {
+        Ok(Ok(result)) => result,
+        Ok(Err(panic)) => Err(Error::Platform(panic_message(panic.as_ref()))),
+        Err(Some(exception)) => {
+            Err(Error::Platform(format!("{:?}", exception)))
         }
+        Err(None) => Err(platform("nil exception")),
+    }
+}
+
+/// Logs a warning once, in debug builds with the `log` feature, when the
+/// pasteboard is used from a thread other than the main one that is not
+/// running a run loop.
+///
+/// `Clipboard` is `Send` and `Sync`, but lazily provided pasteboard data is
+/// delivered through run loops, so such accesses may stall or observe stale
+/// contents.
+#[cfg(all(debug_assertions, feature = "log"))]
+fn check_thread() {
+    use objc2_foundation::{NSRunLoop, NSThread};
+    use std::sync::Once;
+
+    static WARNING: Once = Once::new();
+
+    if NSThread::isMainThread_class() {
+        return;
+    }
+
+    let is_running =
+        unsafe { NSRunLoop::currentRunLoop().currentMode() }.is_some();
+
+    if !is_running {
+        WARNING.call_once(|| {
+            log::warn!(
+                "the pasteboard is being used from {:?}, \
+                 which is not the main thread and is not running a run loop; \
+                 lazily provided pasteboard data may never arrive",
+                std::thread::current(),
+            );
+        });
+    }
+}
+
+#[cfg(not(all(debug_assertions, feature = "log")))]
+fn check_thread() {}
+
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        format!("panicked: {}", message)
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        format!("panicked: {}", message)
+    } else {
+        "panicked".to_owned()
     }
 }
@@ -0,0 +1,62 @@
+use objc2::rc::Id;
+use objc2::runtime::AnyObject;
+use objc2_app_kit::{
+    NSPasteboardReadingOptionKey,
+    NSPasteboardURLReadingContentsConformToTypesKey,
+    NSPasteboardURLReadingFileURLsOnlyKey,
+};
+use objc2_foundation::{NSArray, NSDictionary, NSNumber, NSString};
+
+/// Options that control how objects are read from the pasteboard.
+///
+/// They are passed as the `NSPasteboardReadingOptionKey` dictionary of
+/// `readObjectsForClasses:options:`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReadOptions {
+    /// Only read URLs that point to files.
+    pub file_urls_only: bool,
+    /// Only read URLs whose contents conform to one of these UTIs.
+    pub content_types: Vec<String>,
+    /// Return the paths of copied files as text when the pasteboard holds
+    /// no text, escaped and space-separated like Finder pastes them into
+    /// Terminal.
+    pub file_urls_as_text: bool,
+}
+
+impl ReadOptions {
+    pub(crate) fn to_dictionary(
+        &self,
+    ) -> Option<Id<NSDictionary<NSPasteboardReadingOptionKey, AnyObject>>> {
+        let mut keys = Vec::new();
+        let mut values = Vec::new();
+
+        if self.file_urls_only {
+            keys.push(unsafe { NSPasteboardURLReadingFileURLsOnlyKey });
+            values.push(object(NSNumber::numberWithBool(true)));
+        }
+
+        if !self.content_types.is_empty() {
+            let content_types = self
+                .content_types
+                .iter()
+                .map(|content_type| NSString::from_str(content_type))
+                .collect();
+
+            keys.push(unsafe {
+                NSPasteboardURLReadingContentsConformToTypesKey
+            });
+            values.push(object(NSArray::from_vec(content_types)));
+        }
+
+        if keys.is_empty() {
+            None
+        } else {
+            Some(NSDictionary::from_vec(&keys, values))
+        }
+    }
+}
+
+fn object<T: objc2::Message>(value: Id<T>) -> Id<AnyObject> {
+    // SAFETY: Every Objective-C object is an `AnyObject`.
+    unsafe { Id::cast(value) }
+}
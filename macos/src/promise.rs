@@ -0,0 +1,95 @@
+use crate::Error;
+use objc2::rc::Id;
+use objc2::runtime::NSObject;
+use objc2::{declare_class, msg_send_id, mutability, ClassType, DeclaredClass};
+use objc2_app_kit::{
+    NSPasteboard, NSPasteboardItem, NSPasteboardItemDataProvider,
+    NSPasteboardType,
+};
+use objc2_foundation::{NSData, NSObjectProtocol, NSString};
+
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+
+/// Produces the data of a promised type when a paste requests it.
+///
+/// Returning `None` leaves the type without data for that paste.
+pub type Promise = Arc<dyn Fn() -> Option<Vec<u8>> + Send + Sync>;
+
+thread_local! {
+    /// Whether the current thread is running a [`Promise`].
+    static PROVIDING: Cell<bool> = const { Cell::new(false) };
+}
+
+declare_class!(
+    /// Serves the promised types of a pasteboard item.
+    pub(crate) struct DataProvider;
+
+    unsafe impl ClassType for DataProvider {
+        type Super = NSObject;
+        type Mutability = mutability::InteriorMutable;
+        const NAME: &'static str = "ClipboardMacosDataProvider";
+    }
+
+    impl DeclaredClass for DataProvider {
+        type Ivars = HashMap<String, Promise>;
+    }
+
+    unsafe impl NSObjectProtocol for DataProvider {}
+
+    unsafe impl NSPasteboardItemDataProvider for DataProvider {
+        #[method(pasteboard:item:provideDataForType:)]
+        fn provide_data(
+            &self,
+            _pasteboard: Option<&NSPasteboard>,
+            item: &NSPasteboardItem,
+            type_: &NSPasteboardType,
+        ) {
+            let Some(promise) = self.ivars().get(&type_.to_string()) else {
+                return;
+            };
+
+            PROVIDING.with(|providing| providing.set(true));
+            // A panic must not unwind into AppKit.
+            let data = panic::catch_unwind(AssertUnwindSafe(|| promise()));
+            PROVIDING.with(|providing| providing.set(false));
+
+            if let Ok(Some(data)) = data {
+                let _ = unsafe {
+                    item.setData_forType(&NSData::with_bytes(&data), type_)
+                };
+            }
+        }
+    }
+);
+
+/// Fails if called from a [`Promise`].
+///
+/// AppKit asks for promised data while the pasting application waits on
+/// the pasteboard, so any pasteboard operation of the [`Promise`] could
+/// hang.
+pub(crate) fn check_reentrancy() -> Result<(), Error> {
+    if PROVIDING.with(Cell::get) {
+        Err(Error::Reentrancy)
+    } else {
+        Ok(())
+    }
+}
+
+impl DataProvider {
+    pub(crate) fn new(promises: HashMap<String, Promise>) -> Id<Self> {
+        let this = Self::alloc().set_ivars(promises);
+
+        unsafe { msg_send_id![super(this), init] }
+    }
+
+    /// Returns the promised types.
+    pub(crate) fn types(&self) -> Vec<Id<NSString>> {
+        self.ivars()
+            .keys()
+            .map(|type_| NSString::from_str(type_))
+            .collect()
+    }
+}
@@ -0,0 +1,69 @@
+use crate::Error;
+
+/// UTF-16 text with a byte order mark, or big-endian without one.
+pub const UTF16_EXTERNAL_PLAIN_TEXT: &str = "public.utf16-external-plain-text";
+
+/// UTF-16 text in native byte order.
+pub const UTF16_PLAIN_TEXT: &str = "public.utf16-plain-text";
+
+/// Decodes UTF-16 `bytes`, honoring a leading byte order mark.
+///
+/// Without one, the text is assumed to be little-endian only if
+/// `little_endian` is set.
+pub fn decode_utf16(
+    bytes: &[u8],
+    little_endian: bool,
+) -> Result<String, Error> {
+    let (bytes, little_endian) = match bytes {
+        [0xFF, 0xFE, rest @ ..] => (rest, true),
+        [0xFE, 0xFF, rest @ ..] => (rest, false),
+        _ => (bytes, little_endian),
+    };
+
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| {
+            let pair = [pair[0], pair[1]];
+
+            if little_endian {
+                u16::from_le_bytes(pair)
+            } else {
+                u16::from_be_bytes(pair)
+            }
+        })
+        .collect();
+
+    Ok(String::from_utf16(&units)?)
+}
+
+/// Encodes `text` as UTF-16 for the given pasteboard `type_`.
+///
+/// External representations get a big-endian byte order mark, while the
+/// plain type is written in native byte order.
+pub fn encode_utf16(text: &str, type_: &str) -> Vec<u8> {
+    let units = text.encode_utf16();
+
+    if type_ == UTF16_EXTERNAL_PLAIN_TEXT {
+        std::iter::once(0xFEFF)
+            .chain(units)
+            .flat_map(u16::to_be_bytes)
+            .collect()
+    } else {
+        units.flat_map(u16::to_ne_bytes).collect()
+    }
+}
+
+/// Escapes the shell metacharacters of `path` with backslashes.
+pub fn shell_escape(path: &str) -> String {
+    let mut escaped = String::with_capacity(path.len());
+
+    for c in path.chars() {
+        if c.is_whitespace() || "\\'\"`$&|;<>()[]{}*?!#~".contains(c) {
+            escaped.push('\\');
+        }
+
+        escaped.push(c);
+    }
+
+    escaped
+}
@@ -0,0 +1,392 @@
+use crate::{Clipboard, Error};
+
+use objc2_app_kit::NSRunningApplication;
+use std::convert::TryFrom;
+use std::sync::atomic::{AtomicBool, AtomicIsize, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Condvar, Mutex, MutexGuard, PoisonError, Weak};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// How often a [`ClipboardWatcher`] polls the pasteboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interval {
+    /// Polls at the same interval at all times.
+    Fixed(Duration),
+    /// Polls every `active` interval while the application is active, and
+    /// slows down to every `inactive` interval while it is in the
+    /// background.
+    ///
+    /// Users rarely copy into the clipboard of an application they are not
+    /// using, so this saves battery without delaying changes made in the
+    /// application itself.
+    Adaptive {
+        /// The interval while the application is active.
+        active: Duration,
+        /// The interval while the application is in the background.
+        inactive: Duration,
+    },
+}
+
+impl Interval {
+    /// Returns the interval to wait before the next poll.
+    fn current(self) -> Duration {
+        match self {
+            Interval::Fixed(interval) => interval,
+            Interval::Adaptive { active, inactive } => {
+                let is_active = unsafe {
+                    NSRunningApplication::currentApplication().isActive()
+                };
+
+                if is_active {
+                    active
+                } else {
+                    inactive
+                }
+            }
+        }
+    }
+}
+
+impl From<Duration> for Interval {
+    fn from(interval: Duration) -> Self {
+        Interval::Fixed(interval)
+    }
+}
+
+/// A change of the pasteboard reported by a [`ClipboardWatcher`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    /// What changed.
+    pub kind: ChangeKind,
+    /// Whether the change was made by this process, as told by
+    /// [`Clipboard::owns_contents`].
+    ///
+    /// Applications that write the pasteboard themselves can skip these to
+    /// avoid feedback loops.
+    pub originated_locally: bool,
+    /// The bundle identifier of the application that wrote the contents,
+    /// if it declared one as the `org.nspasteboard.source` type.
+    ///
+    /// This follows a convention of nspasteboard.org that macOS does not
+    /// enforce, so it is best-effort: many applications leave it out, and
+    /// any application can claim to be another.
+    pub source: Option<String>,
+}
+
+/// What changed in the pasteboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// The pasteboard was cleared and written again, like by a new copy.
+    Content,
+    /// The pasteboard declares every type it declared before along with
+    /// new ones, like when a lazy provider adds a type.
+    ///
+    /// Clipboard history tools can ignore these to avoid capturing the same
+    /// copy twice. Types are only compared once `changeCount` moves, so
+    /// types added without moving it are not reported, and a new copy
+    /// that declares more types than the previous one is taken for a
+    /// change of formats.
+    Formats,
+}
+
+/// Reports changes of the general pasteboard by polling its `changeCount`
+/// from a background thread.
+///
+/// Only the general pasteboard is watched. macOS has no PRIMARY selection,
+/// and the drag, find and font pasteboards are not watched.
+///
+/// The thread stops once the [`ClipboardWatcher`] is dropped.
+pub struct ClipboardWatcher {
+    clipboard: Arc<Clipboard>,
+    state: Arc<State>,
+    thread: Option<JoinHandle<()>>,
+}
+
+/// The health of a [`ClipboardWatcher`], as returned by
+/// [`ClipboardWatcher::health`].
+///
+/// The pasteboard is polled rather than listened to, so there is no
+/// connection that can drop or be reestablished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Health {
+    /// The number of times the pasteboard has been polled.
+    pub polls: u64,
+    /// The number of polls skipped because the watcher thread was not
+    /// scheduled in time, like while the system was asleep.
+    pub missed_polls: u64,
+    /// Whether the watcher thread is still polling.
+    ///
+    /// The thread only stops early if a callback panics, after which no
+    /// more changes are reported.
+    pub is_running: bool,
+}
+
+/// A callback notified of pasteboard changes.
+type Callback = Box<dyn FnMut(Change) + Send>;
+
+/// The state shared between a [`ClipboardWatcher`] and its thread.
+struct State {
+    /// The callbacks to notify, with the identifier of their
+    /// [`Subscription`].
+    subscribers: Mutex<Vec<(u64, Callback)>>,
+    next_id: AtomicU64,
+    /// The last `changeCount` seen, by the thread or on resume.
+    last: AtomicIsize,
+    /// Whether the watcher was dropped, notified to wake the thread from
+    /// its wait.
+    is_stopped: (Mutex<bool>, Condvar),
+    is_paused: AtomicBool,
+    polls: AtomicU64,
+    missed_polls: AtomicU64,
+}
+
+impl State {
+    fn subscribers(&self) -> MutexGuard<'_, Vec<(u64, Callback)>> {
+        self.subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Waits for `timeout`, returning early with `true` once the watcher
+    /// is stopped.
+    fn wait_for_stop(&self, timeout: Duration) -> bool {
+        let (is_stopped, condvar) = &self.is_stopped;
+        let is_stopped =
+            is_stopped.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let (is_stopped, _) = condvar
+            .wait_timeout_while(is_stopped, timeout, |is_stopped| !*is_stopped)
+            .unwrap_or_else(PoisonError::into_inner);
+
+        *is_stopped
+    }
+
+    fn stop(&self) {
+        let (is_stopped, condvar) = &self.is_stopped;
+
+        *is_stopped.lock().unwrap_or_else(PoisonError::into_inner) = true;
+        condvar.notify_all();
+    }
+}
+
+impl ClipboardWatcher {
+    /// Starts polling the pasteboard at the given [`Interval`], calling
+    /// `on_change` with the [`Change`] whenever the pasteboard has changed
+    /// since the last poll.
+    pub fn spawn(
+        interval: impl Into<Interval>,
+        on_change: impl FnMut(Change) + Send + 'static,
+    ) -> Result<Self, Error> {
+        let interval = interval.into();
+        let clipboard = Arc::new(Clipboard::new()?);
+        let state = Arc::new(State {
+            subscribers: Mutex::new(vec![(0, Box::new(on_change) as Callback)]),
+            next_id: AtomicU64::new(1),
+            last: AtomicIsize::new(clipboard.change_count()),
+            is_stopped: (Mutex::new(false), Condvar::new()),
+            is_paused: AtomicBool::new(false),
+            polls: AtomicU64::new(0),
+            missed_polls: AtomicU64::new(0),
+        });
+
+        let thread = thread::Builder::new()
+            .name("clipboard_macos watcher".to_owned())
+            .spawn({
+                let clipboard = Arc::clone(&clipboard);
+                let state = Arc::clone(&state);
+
+                move || {
+                    let mut types = declared_types(&clipboard);
+
+                    loop {
+                        let wait = interval.current();
+                        let start = Instant::now();
+
+                        if state.wait_for_stop(wait) {
+                            break;
+                        }
+
+                        // A sleep that overran by whole intervals, like
+                        // while the system was asleep, skipped those polls.
+                        let overrun =
+                            start.elapsed().as_nanos() / wait.as_nanos().max(1);
+                        let missed = u64::try_from(overrun.saturating_sub(1))
+                            .unwrap_or(u64::MAX);
+
+                        let _ = state.polls.fetch_add(1, Ordering::Relaxed);
+                        let _ = state
+                            .missed_polls
+                            .fetch_add(missed, Ordering::Relaxed);
+
+                        let current = clipboard.change_count();
+                        let last = state.last.swap(current, Ordering::SeqCst);
+
+                        // The types are only worth querying, a round trip
+                        // to the pasteboard server, once the count moves.
+                        if current == last {
+                            continue;
+                        }
+
+                        let previous_types = std::mem::replace(
+                            &mut types,
+                            declared_types(&clipboard),
+                        );
+
+                        let kind = if !previous_types.is_empty()
+                            && types.len() > previous_types.len()
+                            && previous_types
+                                .iter()
+                                .all(|previous| types.contains(previous))
+                        {
+                            ChangeKind::Formats
+                        } else {
+                            ChangeKind::Content
+                        };
+
+                        if !state.is_paused.load(Ordering::SeqCst) {
+                            let change = Change {
+                                kind,
+                                originated_locally: clipboard.owns_contents(),
+                                source: source(&clipboard),
+                            };
+                            let mut subscribers = state.subscribers();
+
+                            for (_, on_change) in subscribers.iter_mut() {
+                                on_change(change.clone());
+                            }
+                        }
+                    }
+                }
+            })?;
+
+        Ok(ClipboardWatcher {
+            clipboard,
+            state,
+            thread: Some(thread),
+        })
+    }
+
+    /// Starts polling the pasteboard at the given [`Interval`], sending each
+    /// [`Change`] through the returned channel.
+    pub fn channel(
+        interval: impl Into<Interval>,
+    ) -> Result<(Self, mpsc::Receiver<Change>), Error> {
+        let (sender, receiver) = mpsc::channel();
+
+        Ok((Self::with_sender(interval, sender)?, receiver))
+    }
+
+    /// Starts polling the pasteboard at the given [`Interval`], sending each
+    /// [`Change`] through `sender`.
+    ///
+    /// This lets an existing event loop receive changes on its own channel.
+    /// Other channels, like the ones of `crossbeam`, can be fed from a
+    /// [`ClipboardWatcher::spawn`] callback instead.
+    pub fn with_sender(
+        interval: impl Into<Interval>,
+        sender: mpsc::Sender<Change>,
+    ) -> Result<Self, Error> {
+        Self::spawn(interval, move |change| {
+            let _ = sender.send(change);
+        })
+    }
+
+    /// Calls `on_change` too with every [`Change`], until the returned
+    /// [`Subscription`] is dropped.
+    ///
+    /// Every subscriber shares the polling thread of the
+    /// [`ClipboardWatcher`], so independent parts of an application can
+    /// watch the pasteboard without each polling it. Callbacks run on that
+    /// thread, one after another, and must not subscribe or drop a
+    /// [`Subscription`] themselves.
+    pub fn subscribe(
+        &self,
+        on_change: impl FnMut(Change) + Send + 'static,
+    ) -> Subscription {
+        let id = self.state.next_id.fetch_add(1, Ordering::Relaxed);
+
+        self.state.subscribers().push((id, Box::new(on_change)));
+
+        Subscription {
+            id,
+            state: Arc::downgrade(&self.state),
+        }
+    }
+
+    /// Stops reporting changes until [`ClipboardWatcher::resume`] is called.
+    ///
+    /// Changes made while paused are never reported, not even after
+    /// resuming, so an application can pause the watcher around its own
+    /// writes, or while the user session is locked.
+    pub fn pause(&self) {
+        self.state.is_paused.store(true, Ordering::SeqCst);
+    }
+
+    /// Resumes reporting changes after [`ClipboardWatcher::pause`].
+    pub fn resume(&self) {
+        self.state
+            .last
+            .store(self.clipboard.change_count(), Ordering::SeqCst);
+        self.state.is_paused.store(false, Ordering::SeqCst);
+    }
+
+    /// Returns the [`Health`] of the [`ClipboardWatcher`], so long-running
+    /// applications can tell when it stopped reporting changes.
+    pub fn health(&self) -> Health {
+        Health {
+            polls: self.state.polls.load(Ordering::Relaxed),
+            missed_polls: self.state.missed_polls.load(Ordering::Relaxed),
+            is_running: matches!(
+                &self.thread,
+                Some(thread) if !thread.is_finished()
+            ),
+        }
+    }
+
+    /// Returns whether the [`ClipboardWatcher`] is paused.
+    pub fn is_paused(&self) -> bool {
+        self.state.is_paused.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for ClipboardWatcher {
+    fn drop(&mut self) {
+        self.state.stop();
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Returns the types declared on the pasteboard, or none if they cannot be
+/// queried.
+fn declared_types(clipboard: &Clipboard) -> Vec<String> {
+    clipboard.declared_types().unwrap_or_default()
+}
+
+/// Returns the bundle identifier declared as the source of the contents.
+fn source(clipboard: &Clipboard) -> Option<String> {
+    let data = clipboard.read_data("org.nspasteboard.source").ok()?;
+
+    String::from_utf8(data)
+        .ok()
+        .filter(|source| !source.is_empty())
+}
+
+/// A callback added to a [`ClipboardWatcher`] by
+/// [`ClipboardWatcher::subscribe`].
+///
+/// The callback is removed once the [`Subscription`] is dropped.
+pub struct Subscription {
+    id: u64,
+    state: Weak<State>,
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        if let Some(state) = self.state.upgrade() {
+            state.subscribers().retain(|(id, _)| *id != self.id);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Contents composed of several representations.
+use crate::formats::Format;
+use crate::platform;
+
+use std::borrow::Cow;
+
+/// Contents to write to the clipboard as a single item with several
+/// representations, so each application pastes the richest one it
+/// understands.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ClipboardContent {
+    pub(crate) text: Option<String>,
+    pub(crate) representations: Vec<(Format, Vec<u8>)>,
+}
+
+impl ClipboardContent {
+    /// Creates new, empty [`ClipboardContent`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the plain text of the contents.
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    /// Adds an HTML fragment, in the native HTML representation.
+    pub fn html(self, html: &str) -> Self {
+        let (format, data) = platform::html_data(html);
+
+        self.with(format, data)
+    }
+
+    /// Adds an RTF document.
+    pub fn rtf(self, rtf: impl Into<Vec<u8>>) -> Self {
+        self.with(Format::new(platform::RTF), rtf.into())
+    }
+
+    /// Adds a PNG image.
+    pub fn png(self, png: impl Into<Vec<u8>>) -> Self {
+        self.with(Format::new(platform::PNG), png.into())
+    }
+
+    /// Adds data in a custom format, identified by its platform-native
+    /// name.
+    pub fn custom(
+        self,
+        format: impl Into<Cow<'static, str>>,
+        data: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.with(Format::new(format), data.into())
+    }
+
+    /// Returns whether no representation was added.
+    pub fn is_empty(&self) -> bool {
+        self.text.is_none() && self.representations.is_empty()
+    }
+
+    /// Adds a representation, replacing any previous one of the same
+    /// [`Format`].
+    fn with(mut self, format: Format, data: Vec<u8>) -> Self {
+        self.representations
+            .retain(|(candidate, _)| *candidate != format);
+        self.representations.push((format, data));
+        self
+    }
+}
@@ -0,0 +1,7 @@
+//! Accessibility descriptions of clipboard contents.
+
+/// The format of the description written along with the contents.
+///
+/// Its data is a short, human-readable label of the contents in UTF-8,
+/// like `image: bar chart of Q3 sales`, for assistive tools to announce.
+pub const FORMAT: &str = "application/x-window-clipboard-description";
@@ -0,0 +1,227 @@
+use std::fmt;
+
+/// An error produced by a [`Clipboard`](crate::Clipboard) operation.
+///
+/// Besides the underlying platform error, it records the [`Context`] in
+/// which the failure happened.
+#[derive(Debug)]
+pub struct Error {
+    context: Context,
+    kind: ErrorKind,
+    source: Box<dyn std::error::Error>,
+}
+
+impl Error {
+    pub(crate) fn new(
+        operation: Operation,
+        backend: &'static str,
+        format: Option<String>,
+        source: Box<dyn std::error::Error>,
+    ) -> Self {
+        let code = code(source.as_ref());
+        let kind = kind(source.as_ref()).or(code.map(ErrorKind::Os)).unwrap_or(
+            match operation {
+                Operation::Connect => ErrorKind::BackendUnavailable,
+                _ => ErrorKind::Other,
+            },
+        );
+
+        Error {
+            context: Context {
+                operation,
+                backend,
+                format,
+                code,
+            },
+            kind,
+            source,
+        }
+    }
+
+    /// Returns the [`Context`] of the failed operation.
+    pub fn context(&self) -> &Context {
+        &self.context
+    }
+
+    /// Returns the [`ErrorKind`] of the failure.
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.context, self.source)
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+/// The kind of an [`Error`], telling failures apart without inspecting
+/// their source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The clipboard holds no data in the requested format.
+    Empty,
+    /// The requested format is not supported by the backend.
+    UnsupportedType,
+    /// The backend could not be reached, or is not implemented on this
+    /// platform.
+    BackendUnavailable,
+    /// The data could not be converted, like text that is not valid UTF-8.
+    ConversionFailed,
+    /// The [`Clipboard`](crate::Clipboard) refused the contents, because
+    /// they are quarantined, expired or too large, or refused the
+    /// operation because of its [`Mode`](crate::Mode).
+    Refused,
+    /// The platform reported an error code.
+    Os(PlatformCode),
+    /// Any other failure.
+    Other,
+}
+
+/// The circumstances of a failed clipboard operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Context {
+    /// The operation that was attempted.
+    pub operation: Operation,
+    /// The name of the backend that performed the operation.
+    pub backend: &'static str,
+    /// The format involved in the operation, if any.
+    pub format: Option<String>,
+    /// The error code reported by the platform, if any.
+    pub code: Option<PlatformCode>,
+}
+
+impl fmt::Display for Context {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} failed (backend: {}", self.operation, self.backend)?;
+
+        if let Some(format) = &self.format {
+            write!(f, ", format: {}", format)?;
+        }
+
+        if let Some(code) = self.code {
+            write!(f, ", {}", code)?;
+        }
+
+        write!(f, ")")
+    }
+}
+
+/// An error code reported by the platform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlatformCode {
+    /// An operating system error, like an `errno` value.
+    Os(i32),
+    /// A Win32 error code, as returned by `GetLastError`.
+    Win32(u32),
+    /// An error reported by the X11 server.
+    X11 {
+        /// The error code, as it appears on the wire.
+        error_code: u8,
+        /// The major opcode of the failed request.
+        major_opcode: u8,
+        /// The minor opcode of the failed request.
+        minor_opcode: u16,
+    },
+}
+
+impl fmt::Display for PlatformCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlatformCode::Os(code) => write!(f, "os error {}", code),
+            PlatformCode::Win32(code) => write!(f, "win32 error {}", code),
+            PlatformCode::X11 {
+                error_code,
+                major_opcode,
+                minor_opcode,
+            } => write!(
+                f,
+                "x11 error {} (request {}.{})",
+                error_code, major_opcode, minor_opcode
+            ),
+        }
+    }
+}
+
+/// A clipboard operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    Connect,
+    Read,
+    Write,
+    ReadPrimary,
+    WritePrimary,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Operation::Connect => "connect",
+            Operation::Read => "read",
+            Operation::Write => "write",
+            Operation::ReadPrimary => "read primary",
+            Operation::WritePrimary => "write primary",
+        })
+    }
+}
+
+/// Finds the first platform error code in the chain of `error`.
+fn code(error: &(dyn std::error::Error + 'static)) -> Option<PlatformCode> {
+    let mut current = Some(error);
+
+    while let Some(error) = current {
+        if let Some(code) = crate::platform::error_code(error).or_else(|| {
+            error
+                .downcast_ref::<std::io::Error>()
+                .and_then(std::io::Error::raw_os_error)
+                .map(PlatformCode::Os)
+        }) {
+            return Some(code);
+        }
+
+        current = error.source();
+    }
+
+    None
+}
+
+/// Finds the first error of a known kind in the chain of `error`.
+fn kind(error: &(dyn std::error::Error + 'static)) -> Option<ErrorKind> {
+    let mut current = Some(error);
+
+    while let Some(error) = current {
+        if let Some(kind) = crate::platform::error_kind(error) {
+            return Some(kind);
+        }
+
+        if error.is::<crate::quarantine::Quarantined>()
+            || error.is::<crate::mode::ReadOnly>()
+            || error.is::<crate::mode::WriteOnly>()
+            || error.is::<crate::expiry::Expired>()
+            || error.is::<crate::image::TooLarge>()
+            || error.is::<crate::image::UnreadableHeader>()
+        {
+            return Some(ErrorKind::Refused);
+        }
+
+        if error.is::<std::string::FromUtf8Error>()
+            || error.is::<std::str::Utf8Error>()
+        {
+            return Some(ErrorKind::ConversionFailed);
+        }
+
+        if error.is::<crate::internal::Error>() {
+            return Some(ErrorKind::Empty);
+        }
+
+        current = error.source();
+    }
+
+    None
+}
@@ -0,0 +1,101 @@
+//! Expiry of clipboard contents.
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The format of the expiry written along with the contents.
+///
+/// Its data is the expiry time, as the number of seconds since the Unix
+/// epoch in ASCII decimal digits.
+pub const FORMAT: &str = "application/x-window-clipboard-expiry";
+
+/// The error of a read refused because the contents have expired.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("clipboard contents expired")]
+pub struct Expired;
+
+/// The pending clear of a write made by
+/// [`Clipboard::write_with_ttl`](crate::Clipboard::write_with_ttl).
+///
+/// Dropping the [`ClearTimer`] leaves it running. Use [`ClearTimer::cancel`]
+/// to keep the contents.
+#[derive(Debug)]
+pub struct ClearTimer {
+    is_cancelled: Arc<(Mutex<bool>, Condvar)>,
+    thread: JoinHandle<()>,
+}
+
+impl ClearTimer {
+    /// Calls `on_expiry` from a background thread once `ttl` has elapsed,
+    /// unless the timer is cancelled first.
+    #[cfg(any(
+        windows,
+        all(
+            unix,
+            not(any(
+                target_os = "ios",
+                target_os = "android",
+                target_os = "emscripten",
+                target_os = "redox"
+            ))
+        )
+    ))]
+    pub(crate) fn spawn(
+        ttl: Duration,
+        on_expiry: impl FnOnce() + Send + 'static,
+    ) -> Self {
+        let is_cancelled = Arc::new((Mutex::new(false), Condvar::new()));
+
+        let thread = std::thread::spawn({
+            let is_cancelled = Arc::clone(&is_cancelled);
+
+            move || {
+                let (lock, condvar) = &*is_cancelled;
+                let guard = lock.lock().unwrap_or_else(PoisonError::into_inner);
+                let (guard, _) = condvar
+                    .wait_timeout_while(guard, ttl, |is_cancelled| {
+                        !*is_cancelled
+                    })
+                    .unwrap_or_else(PoisonError::into_inner);
+
+                if !*guard {
+                    drop(guard);
+                    on_expiry();
+                }
+            }
+        });
+
+        ClearTimer {
+            is_cancelled,
+            thread,
+        }
+    }
+
+    /// Cancels the timer, leaving the contents in the clipboard.
+    ///
+    /// It has no effect if the clipboard was already cleared.
+    pub fn cancel(self) {
+        let (lock, condvar) = &*self.is_cancelled;
+
+        *lock.lock().unwrap_or_else(PoisonError::into_inner) = true;
+        condvar.notify_all();
+
+        let _ = self.thread.join();
+    }
+}
+
+/// Encodes an expiry time as the data of [`FORMAT`].
+pub fn encode(expires_at: SystemTime) -> Vec<u8> {
+    let seconds = expires_at
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+
+    seconds.to_string().into_bytes()
+}
+
+/// Decodes the data of [`FORMAT`] into an expiry time.
+pub fn decode(data: &[u8]) -> Option<SystemTime> {
+    let seconds = std::str::from_utf8(data).ok()?.trim().parse().ok()?;
+
+    UNIX_EPOCH.checked_add(Duration::from_secs(seconds))
+}
@@ -0,0 +1,365 @@
+//! File lists.
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// The files of a pasted file list.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileList {
+    /// The local paths of the files, in the order they were listed.
+    pub paths: Vec<PathBuf>,
+    /// The entries that were not accepted as local paths.
+    pub rejected: Vec<Rejected>,
+}
+
+/// An entry of a file list that was not accepted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rejected {
+    /// The entry, as it was listed.
+    pub entry: String,
+    /// Why the entry was not accepted.
+    pub reason: Reason,
+}
+
+/// Why an entry of a file list was not accepted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reason {
+    /// The URL has a scheme other than `file`.
+    UnsupportedScheme(String),
+    /// The URL names a host other than the local one.
+    RemoteHost(String),
+    /// The URL has a malformed percent-encoding, or it encodes a nul byte
+    /// or invalid UTF-8.
+    InvalidEncoding,
+    /// The URL has no absolute path.
+    RelativePath,
+    /// A segment of the path is `.` or `..` only once decoded, or, on
+    /// Windows, holds a `\` or a `:` outside of the leading drive.
+    InvalidSegment(String),
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reason::UnsupportedScheme(scheme) => {
+                write!(f, "unsupported scheme: {}", scheme)
+            }
+            Reason::RemoteHost(host) => write!(f, "remote host: {}", host),
+            Reason::InvalidEncoding => f.write_str("invalid encoding"),
+            Reason::RelativePath => f.write_str("relative path"),
+            Reason::InvalidSegment(segment) => {
+                write!(f, "invalid path segment: {}", segment)
+            }
+        }
+    }
+}
+
+/// Parses a `text/uri-list`, as described by RFC 2483.
+///
+/// Only `file` URLs of the local host are accepted. Their paths are
+/// percent-decoded, and `.` and `..` segments are resolved without ever
+/// leaving the root, or the drive on Windows. Segments that could climb
+/// out of or replace the root once decoded are rejected.
+pub fn parse_uri_list(list: &str) -> FileList {
+    let mut files = FileList::default();
+
+    for entry in list.lines().map(str::trim) {
+        if entry.is_empty() || entry.starts_with('#') {
+            continue;
+        }
+
+        match parse_file_url(entry) {
+            Ok(path) => files.paths.push(path),
+            Err(reason) => files.rejected.push(Rejected {
+                entry: entry.to_owned(),
+                reason,
+            }),
+        }
+    }
+
+    files
+}
+
+/// Formats local paths as a `text/uri-list` of `file` URLs.
+pub fn to_uri_list(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|path| format!("file://{}\r\n", percent_encode(&url_path(path))))
+        .collect()
+}
+
+/// Formats local paths as the `DROPFILES` structure of a Windows
+/// `CF_HDROP`, with wide-character paths.
+pub(crate) fn to_drop_files(paths: &[PathBuf]) -> Vec<u8> {
+    // The offset of the paths, a drop point of (0, 0) in client
+    // coordinates, and the flag of wide characters
+    let header = [20u32, 0, 0, 0, 1];
+
+    header
+        .into_iter()
+        .flat_map(u32::to_le_bytes)
+        .chain(
+            paths
+                .iter()
+                .flat_map(|path| {
+                    let path = path.to_string_lossy();
+
+                    path.encode_utf16().chain([0]).collect::<Vec<_>>()
+                })
+                .chain([0])
+                .flat_map(u16::to_le_bytes),
+        )
+        .collect()
+}
+
+#[cfg(windows)]
+fn url_path(path: &Path) -> String {
+    // `C:\Users` is named by `file:///C:/Users`.
+    format!("/{}", path.to_string_lossy().replace('\\', "/"))
+}
+
+#[cfg(not(windows))]
+fn url_path(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+fn percent_encode(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~:".contains(&byte) {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+
+    encoded
+}
+
+fn parse_file_url(url: &str) -> Result<PathBuf, Reason> {
+    let (scheme, rest) = url.split_once(':').ok_or(Reason::RelativePath)?;
+
+    if !scheme.eq_ignore_ascii_case("file") {
+        return Err(Reason::UnsupportedScheme(scheme.to_owned()));
+    }
+
+    let path = match rest.strip_prefix("//") {
+        Some(rest) => {
+            let (host, path) =
+                rest.split_at(rest.find('/').unwrap_or(rest.len()));
+
+            if !host.is_empty() && !host.eq_ignore_ascii_case("localhost") {
+                return Err(Reason::RemoteHost(host.to_owned()));
+            }
+
+            path
+        }
+        None => rest,
+    };
+
+    // Queries and fragments are not part of the path.
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+
+    if !path.starts_with('/') {
+        return Err(Reason::RelativePath);
+    }
+
+    let mut segments: Vec<String> = Vec::new();
+    // The number of leading segments that `..` never removes.
+    let mut root = 0;
+
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => continue,
+            ".." => {
+                if segments.len() > root {
+                    let _ = segments.pop();
+                }
+
+                continue;
+            }
+            _ => {}
+        }
+
+        let decoded = percent_decode(segment)?;
+
+        if matches!(decoded.as_str(), "." | "..") {
+            return Err(Reason::InvalidSegment(segment.to_owned()));
+        }
+
+        if cfg!(windows) {
+            if segments.is_empty() && is_drive(&decoded) {
+                root = 1;
+            } else if decoded.contains(['\\', ':']) {
+                return Err(Reason::InvalidSegment(segment.to_owned()));
+            }
+        }
+
+        segments.push(decoded);
+    }
+
+    Ok(to_path(segments))
+}
+
+/// Returns whether a segment names a drive, like `C:`.
+fn is_drive(segment: &str) -> bool {
+    let bytes = segment.as_bytes();
+
+    bytes.len() == 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':'
+}
+
+#[cfg(windows)]
+fn to_path(segments: Vec<String>) -> PathBuf {
+    // `file:///C:/Users` names `C:\Users`.
+    match segments.first() {
+        Some(drive) if is_drive(drive) => {
+            let mut path = PathBuf::from(format!("{}\\", drive));
+            path.extend(&segments[1..]);
+            path
+        }
+        _ => PathBuf::from("\\").join(segments.join("\\")),
+    }
+}
+
+#[cfg(not(windows))]
+fn to_path(segments: Vec<String>) -> PathBuf {
+    PathBuf::from("/").join(segments.join("/"))
+}
+
+fn percent_decode(segment: &str) -> Result<String, Reason> {
+    let mut bytes = Vec::with_capacity(segment.len());
+    let mut input = segment.bytes();
+
+    while let Some(byte) = input.next() {
+        if byte != b'%' {
+            bytes.push(byte);
+            continue;
+        }
+
+        let high = input.next().and_then(hex).ok_or(Reason::InvalidEncoding)?;
+        let low = input.next().and_then(hex).ok_or(Reason::InvalidEncoding)?;
+
+        match high << 4 | low {
+            // A decoded separator or nul byte would change what the path
+            // names.
+            0 | b'/' | b'\\' => return Err(Reason::InvalidEncoding),
+            byte => bytes.push(byte),
+        }
+    }
+
+    String::from_utf8(bytes).map_err(|_| Reason::InvalidEncoding)
+}
+
+fn hex(digit: u8) -> Option<u8> {
+    char::from(digit).to_digit(16).map(|digit| digit as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(url: &str) -> Result<PathBuf, Reason> {
+        parse_file_url(url)
+    }
+
+    #[test]
+    fn accepts_local_file_urls() {
+        let files = parse_uri_list(
+            "# comment\r\nfile:///tmp/a%20b\r\nfile://localhost/tmp/c\r\n",
+        );
+
+        assert_eq!(files.rejected, []);
+        assert_eq!(files.paths.len(), 2);
+    }
+
+    #[test]
+    fn rejects_remote_hosts() {
+        assert_eq!(
+            parse("file://host/tmp"),
+            Err(Reason::RemoteHost("host".to_owned()))
+        );
+        assert_eq!(
+            parse("file://host"),
+            Err(Reason::RemoteHost("host".to_owned()))
+        );
+    }
+
+    #[test]
+    fn rejects_other_schemes_and_relative_paths() {
+        assert_eq!(
+            parse("https://example.com/"),
+            Err(Reason::UnsupportedScheme("https".to_owned()))
+        );
+        assert_eq!(parse("file:tmp"), Err(Reason::RelativePath));
+        assert_eq!(parse("file://localhost"), Err(Reason::RelativePath));
+    }
+
+    #[test]
+    fn never_climbs_out_of_the_root() {
+        assert_eq!(
+            parse("file:///../../etc/./passwd"),
+            Ok(to_path(vec!["etc".to_owned(), "passwd".to_owned()]))
+        );
+    }
+
+    #[test]
+    fn rejects_encoded_separators_and_dot_segments() {
+        assert_eq!(parse("file:///a%2Fb"), Err(Reason::InvalidEncoding));
+        assert_eq!(parse("file:///a%5Cb"), Err(Reason::InvalidEncoding));
+        assert_eq!(parse("file:///a%00"), Err(Reason::InvalidEncoding));
+        assert_eq!(parse("file:///a%zz"), Err(Reason::InvalidEncoding));
+        assert_eq!(
+            parse("file:///tmp/%2E%2E/etc"),
+            Err(Reason::InvalidSegment("%2E%2E".to_owned()))
+        );
+        assert_eq!(
+            parse("file:///tmp/%2e"),
+            Err(Reason::InvalidSegment("%2e".to_owned()))
+        );
+    }
+
+    #[test]
+    fn round_trips_paths() {
+        let path = to_path(vec!["tmp".to_owned(), "a b%".to_owned()]);
+        let list = to_uri_list(std::slice::from_ref(&path));
+
+        assert_eq!(parse_uri_list(&list).paths, [path]);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn rejects_windows_separators_and_drives_in_segments() {
+        for url in [
+            "file:///C:/a\\..\\..\\x",
+            "file:///C:/%5Cevil",
+            "file:///C:/\\evil",
+            "file:///C:/D:\\x",
+            "file:///C:/a/D:",
+            "file:///tmp/C:",
+        ] {
+            assert!(
+                matches!(
+                    parse(url),
+                    Err(Reason::InvalidSegment(_) | Reason::InvalidEncoding)
+                ),
+                "{} was accepted",
+                url
+            );
+        }
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn never_leaves_the_drive() {
+        assert_eq!(
+            parse("file:///C:/../../Windows"),
+            Ok(PathBuf::from("C:\\Windows"))
+        );
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn keeps_backslashes_and_colons_on_unix() {
+        assert_eq!(parse("file:///a\\b:c"), Ok(PathBuf::from("/a\\b:c")));
+    }
+}
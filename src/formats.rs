@@ -0,0 +1,272 @@
+//! Clipboard formats.
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// A clipboard format, identified by its platform-native name.
+///
+/// That is a target atom on X11, a MIME type on Wayland, a UTI on macOS and
+/// a clipboard format name on Windows (e.g. `CF_UNICODETEXT`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Format(Cow<'static, str>);
+
+impl Format {
+    /// Creates a [`Format`] from its platform-native name.
+    pub fn new(name: impl Into<Cow<'static, str>>) -> Self {
+        Format(name.into())
+    }
+
+    /// Returns the platform-native name of the [`Format`].
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the normalized MIME type of the [`Format`], if known.
+    ///
+    /// See [`normalize`].
+    pub fn normalized(&self) -> Option<&'static str> {
+        normalize(&self.0)
+    }
+}
+
+/// The data of every readable [`Format`] of the clipboard contents.
+pub type Representations = HashMap<Format, Vec<u8>>;
+
+/// The representations of one item of the clipboard, as pairs of [`Format`]
+/// and data.
+pub type Item = Vec<(Format, Vec<u8>)>;
+
+/// Synthesizes the data of a [`Format`] from the data of another one,
+/// returning `None` if the data cannot be converted.
+pub type Converter = Arc<dyn Fn(&[u8]) -> Option<Vec<u8>> + Send + Sync>;
+
+/// Produces the data of a [`Format`] only once a paste requests it,
+/// returning `None` if the data cannot be produced.
+pub type Promise = Arc<dyn Fn() -> Option<Vec<u8>> + Send + Sync>;
+
+/// The kind of contents in the clipboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContentKind {
+    /// Plain text.
+    PlainText,
+    /// Formatted text, like HTML or RTF.
+    RichText,
+    /// An image.
+    Image,
+    /// A list of files.
+    Files,
+    /// A URL.
+    Url,
+    /// Only formats unknown to [`normalize`].
+    Custom,
+    /// Nothing at all.
+    Empty,
+}
+
+impl ContentKind {
+    /// Classifies contents declared as the given [`Format`]s.
+    ///
+    /// When several kinds are present, the most specific one wins: files,
+    /// then images, rich text, URLs and plain text.
+    pub fn of(formats: &[Format]) -> Self {
+        if formats.is_empty() {
+            return ContentKind::Empty;
+        }
+
+        let normalized: Vec<_> = formats
+            .iter()
+            .filter_map(|format| normalize(format.name()))
+            .collect();
+
+        let declares = |predicate: fn(&str) -> bool| {
+            normalized.iter().any(|&format| predicate(format))
+        };
+
+        if declares(|format| format == "text/uri-list") {
+            ContentKind::Files
+        } else if declares(|format| format.starts_with("image/")) {
+            ContentKind::Image
+        } else if declares(|format| matches!(format, "text/html" | "text/rtf"))
+        {
+            ContentKind::RichText
+        } else if declares(|format| format == "text/x-moz-url") {
+            ContentKind::Url
+        } else if declares(|format| format == "text/plain") {
+            ContentKind::PlainText
+        } else {
+            ContentKind::Custom
+        }
+    }
+}
+
+/// The data read for a [`Format`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Contents {
+    /// The data, in the requested [`Format`].
+    pub data: Vec<u8>,
+    /// The [`Format`] the data was converted from, if it was unavailable in
+    /// the requested one.
+    pub converted_from: Option<Format>,
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The normalized format of every known platform-native format name.
+///
+/// Normalized formats are MIME types without parameters. The table is the
+/// same on every platform, so names of other platforms can be looked up too.
+const NORMALIZATION: &[&[(&str, &str)]] = &[WINDOWS, MACOS, X11, MIME];
+
+/// The normalized format of every known Windows clipboard format name, the
+/// preferred name of each format first.
+pub(crate) const WINDOWS: &[(&str, &str)] = &[
+    ("CF_UNICODETEXT", "text/plain"),
+    ("CF_TEXT", "text/plain"),
+    ("CF_OEMTEXT", "text/plain"),
+    ("CF_HDROP", "text/uri-list"),
+    ("CF_DIB", "image/bmp"),
+    ("CF_DIBV5", "image/bmp"),
+    ("CF_ENHMETAFILE", "image/emf"),
+    ("UniformResourceLocatorW", "text/x-moz-url"),
+    ("HTML Format", "text/html"),
+    ("Rich Text Format", "text/rtf"),
+    ("Csv", "text/csv"),
+    ("MathML", "application/mathml+xml"),
+    ("MathML Presentation", "application/mathml+xml"),
+    ("PNG", "image/png"),
+    ("JFIF", "image/jpeg"),
+    ("GIF", "image/gif"),
+];
+
+/// The normalized format of every known macOS UTI, the preferred UTI of each
+/// format first.
+pub(crate) const MACOS: &[(&str, &str)] = &[
+    ("public.utf8-plain-text", "text/plain"),
+    ("public.utf16-external-plain-text", "text/plain"),
+    ("public.utf16-plain-text", "text/plain"),
+    ("NSStringPboardType", "text/plain"),
+    ("public.file-url", "text/uri-list"),
+    ("public.url", "text/x-moz-url"),
+    ("public.html", "text/html"),
+    ("public.rtf", "text/rtf"),
+    ("public.comma-separated-values-text", "text/csv"),
+    (
+        "public.utf8-tab-separated-values-text",
+        "text/tab-separated-values",
+    ),
+    ("public.png", "image/png"),
+    ("public.tiff", "image/tiff"),
+    ("public.jpeg", "image/jpeg"),
+    ("com.compuserve.gif", "image/gif"),
+    ("com.microsoft.bmp", "image/bmp"),
+    ("public.svg-image", "image/svg+xml"),
+    ("com.adobe.pdf", "application/pdf"),
+    ("public.json", "application/json"),
+    ("public.xml", "application/xml"),
+];
+
+/// The normalized format of the text targets of X11 that are not MIME
+/// types.
+const X11: &[(&str, &str)] = &[
+    ("UTF8_STRING", "text/plain"),
+    ("STRING", "text/plain"),
+    ("TEXT", "text/plain"),
+];
+
+/// The normalized format of the known MIME types, as used by X11 and
+/// Wayland.
+const MIME: &[(&str, &str)] = &[
+    ("text/plain;charset=utf-8", "text/plain"),
+    ("text/plain", "text/plain"),
+    ("text/uri-list", "text/uri-list"),
+    ("text/x-moz-url", "text/x-moz-url"),
+    ("text/html", "text/html"),
+    ("text/rtf", "text/rtf"),
+    ("text/csv", "text/csv"),
+    ("text/tab-separated-values", "text/tab-separated-values"),
+    ("application/mathml+xml", "application/mathml+xml"),
+    ("image/bmp", "image/bmp"),
+    ("image/emf", "image/emf"),
+    ("image/png", "image/png"),
+    ("image/tiff", "image/tiff"),
+    ("image/jpeg", "image/jpeg"),
+    ("image/gif", "image/gif"),
+    ("image/svg+xml", "image/svg+xml"),
+    ("application/pdf", "application/pdf"),
+    ("application/json", "application/json"),
+    ("application/xml", "application/xml"),
+];
+
+/// Returns the normalized format of a platform-native format name, if known
+/// (e.g. `text/plain` for `CF_UNICODETEXT`).
+pub fn normalize(name: &str) -> Option<&'static str> {
+    entries()
+        .find(|&&(platform, _)| platform == name)
+        .map(|&(_, normalized)| normalized)
+}
+
+/// Returns the MIME type of a platform-native format name, without
+/// parameters: its normalized format if known, or the name itself if it is
+/// a MIME type, like `image/webp`.
+pub(crate) fn mime_type(name: &str) -> Option<&str> {
+    normalize(name).or_else(|| {
+        let essence = name.split(';').next().unwrap_or_default().trim();
+
+        essence.contains('/').then_some(essence)
+    })
+}
+
+/// Returns the known platform-native names of a normalized format, in a
+/// fixed order.
+pub fn platform_names(
+    normalized: &str,
+) -> impl Iterator<Item = &'static str> + '_ {
+    entries()
+        .filter(move |&&(_, format)| format == normalized)
+        .map(|&(platform, _)| platform)
+}
+
+/// Returns every entry of [`NORMALIZATION`], in order.
+fn entries() -> impl Iterator<Item = &'static (&'static str, &'static str)> {
+    NORMALIZATION.iter().flat_map(|table| table.iter())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_names_of_every_platform() {
+        assert_eq!(normalize("CF_UNICODETEXT"), Some("text/plain"));
+        assert_eq!(normalize("public.utf8-plain-text"), Some("text/plain"));
+        assert_eq!(normalize("UTF8_STRING"), Some("text/plain"));
+        assert_eq!(normalize("text/plain;charset=utf-8"), Some("text/plain"));
+        assert_eq!(normalize("HTML Format"), Some("text/html"));
+        assert_eq!(normalize("public.png"), Some("image/png"));
+    }
+
+    #[test]
+    fn leaves_unknown_names_unnormalized() {
+        assert_eq!(normalize("com.example.custom"), None);
+        assert_eq!(normalize(""), None);
+    }
+
+    #[test]
+    fn lists_platform_names_of_a_normalized_format() {
+        let names: Vec<_> = platform_names("text/html").collect();
+
+        assert_eq!(names, ["HTML Format", "public.html", "text/html"]);
+    }
+
+    #[test]
+    fn every_normalized_format_normalizes_to_itself() {
+        for &(_, normalized) in entries() {
+            assert_eq!(normalize(normalized), Some(normalized));
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! Syntax highlighting of copied source code.
+use std::fmt::Write;
+use std::sync::OnceLock;
+
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Color, FontStyle, Style, Theme, ThemeSet};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use syntect::util::LinesWithEndings;
+
+/// The theme used to highlight code, which reads well on light
+/// backgrounds like most documents.
+pub const DEFAULT_THEME: &str = "InspiredGitHub";
+
+/// The error of code that could not be highlighted.
+pub type Error = syntect::Error;
+
+/// Source code highlighted as HTML and RTF.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Highlighted {
+    /// The code as an HTML fragment of inline-styled spans.
+    pub html: String,
+    /// The code as an RTF document.
+    pub rtf: String,
+}
+
+/// Highlights `code` written in the given language with the theme of the
+/// given name.
+///
+/// The language is looked up by name, like `Rust`, and then by file
+/// extension, like `rs`. Code of an unknown language is left plain. An
+/// unknown theme falls back to [`DEFAULT_THEME`].
+pub fn highlight(
+    code: &str,
+    language: &str,
+    theme: &str,
+) -> Result<Highlighted, Error> {
+    let syntaxes = syntaxes();
+    let syntax = syntaxes
+        .find_syntax_by_name(language)
+        .or_else(|| syntaxes.find_syntax_by_extension(language))
+        .unwrap_or_else(|| syntaxes.find_syntax_plain_text());
+    let theme = themes()
+        .themes
+        .get(theme)
+        .unwrap_or(&themes().themes[DEFAULT_THEME]);
+
+    Ok(Highlighted {
+        html: syntect::html::highlighted_html_for_string(
+            code, syntaxes, syntax, theme,
+        )?,
+        rtf: rtf(code, syntaxes, syntax, theme)?,
+    })
+}
+
+fn syntaxes() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn themes() -> &'static ThemeSet {
+    static THEMES: OnceLock<ThemeSet> = OnceLock::new();
+
+    THEMES.get_or_init(ThemeSet::load_defaults)
+}
+
+fn rtf(
+    code: &str,
+    syntaxes: &SyntaxSet,
+    syntax: &SyntaxReference,
+    theme: &Theme,
+) -> Result<String, Error> {
+    let mut highlighter = HighlightLines::new(syntax, theme);
+    let mut colors: Vec<Color> = Vec::new();
+    let mut body = String::new();
+
+    for line in LinesWithEndings::from(code) {
+        for (style, text) in highlighter.highlight_line(line, syntaxes)? {
+            let color = color_index(&mut colors, style.foreground);
+
+            // Color 0 is the default one, so the table is 1-indexed.
+            let _ = write!(body, "{{\\cf{}", color + 1);
+            push_font_style(&mut body, style);
+            body.push(' ');
+            push_escaped(&mut body, text);
+            body.push('}');
+        }
+    }
+
+    let mut rtf = String::from(
+        "{\\rtf1\\ansi\\deff0{\\fonttbl{\\f0\\fmodern Courier New;}}",
+    );
+
+    rtf.push_str("{\\colortbl;");
+    for color in colors {
+        let _ =
+            write!(rtf, "\\red{}\\green{}\\blue{};", color.r, color.g, color.b);
+    }
+    rtf.push_str("}\\f0\\fs20 ");
+    rtf.push_str(&body);
+    rtf.push('}');
+
+    Ok(rtf)
+}
+
+fn color_index(colors: &mut Vec<Color>, color: Color) -> usize {
+    match colors.iter().position(|candidate| *candidate == color) {
+        Some(index) => index,
+        None => {
+            colors.push(color);
+            colors.len() - 1
+        }
+    }
+}
+
+fn push_font_style(rtf: &mut String, style: Style) {
+    if style.font_style.contains(FontStyle::BOLD) {
+        rtf.push_str("\\b");
+    }
+
+    if style.font_style.contains(FontStyle::ITALIC) {
+        rtf.push_str("\\i");
+    }
+
+    if style.font_style.contains(FontStyle::UNDERLINE) {
+        rtf.push_str("\\ul");
+    }
+}
+
+fn push_escaped(rtf: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '\\' | '{' | '}' => {
+                rtf.push('\\');
+                rtf.push(c);
+            }
+            '\t' => rtf.push_str("\\tab "),
+            '\n' => rtf.push_str("\\line\n"),
+            '\r' => {}
+            ' '..='~' => rtf.push(c),
+            _ => {
+                // RTF takes signed 16-bit UTF-16 code units, each followed
+                // by a fallback character for older readers.
+                let mut units = [0; 2];
+
+                for unit in c.encode_utf16(&mut units) {
+                    let _ = write!(rtf, "\\u{}?", *unit as i16);
+                }
+            }
+        }
+    }
+}
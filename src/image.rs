@@ -0,0 +1,496 @@
+//! Image header inspection.
+
+use std::cmp;
+
+/// The default maximum number of pixels of an image read from the
+/// clipboard: a 16384×16384 square.
+pub const DEFAULT_MAX_PIXELS: u64 = 1 << 28;
+
+/// The error of a read refused because the image declares more pixels than
+/// allowed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("image too large: {width}x{height} exceeds {max_pixels} pixels")]
+pub struct TooLarge {
+    /// The declared width of the image.
+    pub width: u32,
+    /// The declared height of the image.
+    pub height: u32,
+    /// The maximum number of pixels allowed.
+    pub max_pixels: u64,
+}
+
+/// The error of a read refused because the dimensions of a raster image
+/// cannot be read from its header.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("unreadable image header: {format}")]
+pub struct UnreadableHeader {
+    /// The name of the format of the image.
+    pub format: String,
+}
+
+/// Returns the width and height declared by the header of a PNG, GIF,
+/// JPEG, BMP, TIFF or WebP image, without decoding it.
+///
+/// The dimensions of a TIFF file with several images are those of its
+/// largest one.
+pub fn dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        // The IHDR chunk always comes first.
+        Some((be32(data, 16)?, be32(data, 20)?))
+    } else if data.starts_with(b"GIF8") {
+        Some((le16(data, 6)?.into(), le16(data, 8)?.into()))
+    } else if data.starts_with(&[0xFF, 0xD8]) {
+        jpeg_dimensions(data)
+    } else if data.starts_with(b"BM") {
+        dib_dimensions(data.get(14..)?)
+    } else if data.starts_with(b"II*\0") || data.starts_with(b"MM\0*") {
+        tiff_dimensions(data)
+    } else if data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WEBP") {
+        webp_dimensions(data)
+    } else {
+        None
+    }
+}
+
+/// Returns the width and height declared by a device-independent bitmap
+/// without a file header, like `CF_DIB` and `CF_DIBV5`.
+pub fn dib_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    match le32(data, 0)? {
+        // BITMAPCOREHEADER
+        12 => Some((le16(data, 4)?.into(), le16(data, 6)?.into())),
+        // BITMAPINFOHEADER and its later versions
+        40 | 52 | 56 | 108 | 124 => {
+            let width = le32(data, 4)? as i32;
+            let height = le32(data, 8)? as i32;
+
+            // Negative heights mark top-down bitmaps.
+            Some((width.unsigned_abs(), height.unsigned_abs()))
+        }
+        _ => None,
+    }
+}
+
+/// Checks the declared dimensions of an image in the format of the given
+/// name against `max_pixels`.
+///
+/// Only `CF_DIB` and `CF_DIBV5` are read as headerless bitmaps. Vector
+/// images pass the check. Raster images whose header cannot be read fail
+/// it with [`UnreadableHeader`].
+pub(crate) fn check(
+    format: &str,
+    mime_type: &str,
+    data: &[u8],
+    max_pixels: u64,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if VECTOR.contains(&mime_type) {
+        return Ok(());
+    }
+
+    let dimensions = match format {
+        "CF_DIB" | "CF_DIBV5" => dib_dimensions(data),
+        _ => dimensions(data),
+    };
+
+    match dimensions {
+        Some((width, height)) if pixels((width, height)) > max_pixels => {
+            Err(Box::new(TooLarge {
+                width,
+                height,
+                max_pixels,
+            }))
+        }
+        Some(_) => Ok(()),
+        None => Err(Box::new(UnreadableHeader {
+            format: format.to_owned(),
+        })),
+    }
+}
+
+/// The MIME types of images without pixel dimensions.
+const VECTOR: &[&str] = &[
+    "image/svg+xml",
+    "image/emf",
+    "image/wmf",
+    "image/x-emf",
+    "image/x-wmf",
+];
+
+fn jpeg_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    let mut offset = 2;
+
+    loop {
+        if *data.get(offset)? != 0xFF {
+            return None;
+        }
+
+        // Any number of fill bytes may precede a marker.
+        while *data.get(offset + 1)? == 0xFF {
+            offset += 1;
+        }
+
+        let marker = *data.get(offset + 1)?;
+        offset += 2;
+
+        match marker {
+            // TEM and RSTn stand alone, without a length.
+            0x01 | 0xD0..=0xD7 => continue,
+            // Start of frame markers, except DHT, JPG and DAC.
+            0xC0..=0xCF if !matches!(marker, 0xC4 | 0xC8 | 0xCC) => {
+                let height = be16(data, offset + 3)?;
+                let width = be16(data, offset + 5)?;
+
+                return Some((width.into(), height.into()));
+            }
+            // SOI, EOI or SOS before any frame
+            0xD8..=0xDA => return None,
+            _ => offset += usize::from(be16(data, offset)?),
+        }
+    }
+}
+
+fn tiff_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    let big_endian = data.starts_with(b"MM");
+    let u16_at = |offset| {
+        if big_endian {
+            be16(data, offset)
+        } else {
+            le16(data, offset)
+        }
+    };
+    let u32_at = |offset| {
+        if big_endian {
+            be32(data, offset)
+        } else {
+            le32(data, offset)
+        }
+    };
+
+    let mut largest: Option<(u32, u32)> = None;
+    let mut directory = usize::try_from(u32_at(4)?).ok()?;
+
+    // Every directory describes an image. A chain longer than the limit
+    // is most likely a loop.
+    for _ in 0..MAX_TIFF_DIRECTORIES {
+        let entries = usize::from(u16_at(directory)?);
+
+        let mut width = None;
+        let mut height = None;
+
+        for index in 0..entries {
+            let entry = directory.checked_add(2 + index * 12)?;
+
+            // The value of a SHORT is in the first half of the field.
+            let value = match u16_at(entry + 2)? {
+                3 => u32::from(u16_at(entry + 8)?),
+                4 => u32_at(entry + 8)?,
+                _ => continue,
+            };
+
+            match u16_at(entry)? {
+                256 => width = Some(value),
+                257 => height = Some(value),
+                _ => {}
+            }
+        }
+
+        let image = Some((width?, height?));
+        largest = cmp::max_by_key(largest, image, |image| image.map(pixels));
+
+        let next = directory.checked_add(2 + entries * 12)?;
+
+        match u32_at(next)? {
+            0 => return largest,
+            next => directory = usize::try_from(next).ok()?,
+        }
+    }
+
+    None
+}
+
+const MAX_TIFF_DIRECTORIES: usize = 1024;
+
+fn pixels((width, height): (u32, u32)) -> u64 {
+    u64::from(width) * u64::from(height)
+}
+
+fn webp_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    match data.get(12..16)? {
+        // The canvas size, minus one
+        b"VP8X" => Some((le24(data, 24)? + 1, le24(data, 27)? + 1)),
+        // A lossy key frame, after its tag and start code
+        b"VP8 " => {
+            if data.get(23..26)? != [0x9D, 0x01, 0x2A] {
+                return None;
+            }
+
+            let width = le16(data, 26)? & 0x3FFF;
+            let height = le16(data, 28)? & 0x3FFF;
+
+            Some((width.into(), height.into()))
+        }
+        // A lossless bitstream: 14 bits for each size, minus one
+        b"VP8L" => {
+            if *data.get(20)? != 0x2F {
+                return None;
+            }
+
+            let bits = le32(data, 21)?;
+
+            Some(((bits & 0x3FFF) + 1, ((bits >> 14) & 0x3FFF) + 1))
+        }
+        _ => None,
+    }
+}
+
+fn be16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(
+        data.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn be32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn le16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        data.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn le24(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 3)?;
+
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], 0]))
+}
+
+fn le32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        png.extend(width.to_be_bytes());
+        png.extend(height.to_be_bytes());
+        png
+    }
+
+    fn dib(width: i32, height: i32) -> Vec<u8> {
+        let mut dib = 40u32.to_le_bytes().to_vec();
+        dib.extend(width.to_le_bytes());
+        dib.extend(height.to_le_bytes());
+        dib
+    }
+
+    fn tiff(big_endian: bool, width: u32, height: u16) -> Vec<u8> {
+        let u16 = |value: u16| {
+            if big_endian {
+                value.to_be_bytes()
+            } else {
+                value.to_le_bytes()
+            }
+        };
+        let u32 = |value: u32| {
+            if big_endian {
+                value.to_be_bytes()
+            } else {
+                value.to_le_bytes()
+            }
+        };
+
+        let mut tiff = if big_endian {
+            b"MM\0*".to_vec()
+        } else {
+            b"II*\0".to_vec()
+        };
+        tiff.extend(u32(8));
+        tiff.extend(u16(3));
+
+        // BitsPerSample, ImageWidth as a LONG and ImageLength as a SHORT
+        for (tag, type_, value) in [
+            (258, 3, u16(8).to_vec()),
+            (256, 4, u32(width).to_vec()),
+            (257, 3, u16(height).to_vec()),
+        ] {
+            tiff.extend(u16(tag));
+            tiff.extend(u16(type_));
+            tiff.extend(u32(1));
+
+            let mut field = value;
+            field.resize(4, 0);
+            tiff.extend(field);
+        }
+
+        // No next directory
+        tiff.extend(u32(0));
+        tiff
+    }
+
+    #[test]
+    fn reads_png_dimensions() {
+        assert_eq!(dimensions(&png(640, 480)), Some((640, 480)));
+    }
+
+    #[test]
+    fn reads_gif_dimensions() {
+        let gif = b"GIF89a\x80\x02\xe0\x01";
+
+        assert_eq!(dimensions(gif), Some((640, 480)));
+    }
+
+    #[test]
+    fn reads_jpeg_dimensions() {
+        let jpeg = [
+            0xFF, 0xD8, // SOI
+            0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00, // APP0
+            0xFF, 0xC0, 0x00, 0x11, 0x08, 0x01, 0xE0, 0x02, 0x80, // SOF0
+        ];
+
+        assert_eq!(dimensions(&jpeg), Some((640, 480)));
+    }
+
+    #[test]
+    fn reads_bmp_and_dib_dimensions() {
+        let mut bmp = b"BM".to_vec();
+        bmp.resize(14, 0);
+        bmp.extend(dib(640, -480));
+
+        assert_eq!(dimensions(&bmp), Some((640, 480)));
+        assert_eq!(dib_dimensions(&dib(640, 480)), Some((640, 480)));
+    }
+
+    #[test]
+    fn reads_tiff_dimensions() {
+        let little = tiff(false, 70_000, 480);
+        let big = tiff(true, 70_000, 480);
+
+        assert_eq!(dimensions(&little), Some((70_000, 480)));
+        assert_eq!(dimensions(&big), Some((70_000, 480)));
+    }
+
+    #[test]
+    fn reads_jpeg_dimensions_after_fill_bytes_and_standalone_markers() {
+        let jpeg = [
+            0xFF, 0xD8, // SOI
+            0xFF, 0xFF, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00, // APP0
+            0xFF, 0xD0, // RST0
+            0xFF, 0xFF, 0xC2, 0x00, 0x11, 0x08, 0x01, 0xE0, 0x02,
+            0x80, // SOF2
+        ];
+
+        assert_eq!(dimensions(&jpeg), Some((640, 480)));
+    }
+
+    #[test]
+    fn reads_webp_dimensions() {
+        let webp = |chunk: &[u8], data: &[u8]| {
+            let mut webp = b"RIFF\0\0\0\0WEBP".to_vec();
+            webp.extend(chunk);
+            webp.extend([0; 4]);
+            webp.extend(data);
+            webp
+        };
+
+        let extended =
+            webp(b"VP8X", &[0, 0, 0, 0, 0x7F, 0x02, 0, 0xDF, 0x01, 0]);
+        let lossy = webp(
+            b"VP8 ",
+            &[0, 0, 0, 0x9D, 0x01, 0x2A, 0x80, 0x02, 0xE0, 0x01],
+        );
+        let lossless = webp(
+            b"VP8L",
+            &(0x2F | ((639 | 479 << 14) << 8) as u64).to_le_bytes(),
+        );
+
+        assert_eq!(dimensions(&extended), Some((640, 480)));
+        assert_eq!(dimensions(&lossy), Some((640, 480)));
+        assert_eq!(dimensions(&lossless), Some((640, 480)));
+    }
+
+    #[test]
+    fn reads_the_largest_tiff_image() {
+        let mut tiff = tiff(false, 16, 16);
+
+        // Link a second directory, with a larger image, to the first.
+        let next = tiff.len() as u32;
+        tiff.truncate(tiff.len() - 4);
+        tiff.extend(next.to_le_bytes());
+        tiff.extend(2u16.to_le_bytes());
+
+        for tag in [256u16, 257] {
+            tiff.extend(tag.to_le_bytes());
+            tiff.extend(4u16.to_le_bytes());
+            tiff.extend(1u32.to_le_bytes());
+            tiff.extend(70_000u32.to_le_bytes());
+        }
+
+        tiff.extend(0u32.to_le_bytes());
+
+        assert_eq!(dimensions(&tiff), Some((70_000, 70_000)));
+    }
+
+    #[test]
+    fn does_not_follow_tiff_directory_loops() {
+        let mut tiff = tiff(false, 16, 16);
+        tiff.truncate(tiff.len() - 4);
+        tiff.extend(8u32.to_le_bytes());
+
+        assert_eq!(dimensions(&tiff), None);
+    }
+
+    #[test]
+    fn does_not_read_unknown_data_as_a_dib() {
+        assert_eq!(dimensions(&dib(640, 480)), None);
+        assert!(check("public.jpeg", "image/jpeg", &dib(640, 480), 1).is_err());
+    }
+
+    #[test]
+    fn refuses_images_larger_than_allowed() {
+        let tiff = tiff(false, 1 << 16, 1 << 15);
+        let too_large = |result: Result<(), Box<dyn std::error::Error>>| {
+            result.unwrap_err().is::<TooLarge>()
+        };
+
+        assert!(too_large(check(
+            "public.tiff",
+            "image/tiff",
+            &tiff,
+            1 << 28
+        )));
+        assert!(too_large(check(
+            "CF_DIB",
+            "image/bmp",
+            &dib(1 << 16, 1 << 16),
+            1 << 28
+        )));
+        assert!(check("public.png", "image/png", &png(16, 16), 256).is_ok());
+    }
+
+    #[test]
+    fn refuses_raster_images_with_unreadable_headers() {
+        let unreadable = check("image/webp", "image/webp", b"RIFF", 1 << 28);
+
+        assert!(unreadable.unwrap_err().is::<UnreadableHeader>());
+        assert!(check("image/avif", "image/avif", b"", 1 << 28).is_err());
+        assert!(check("public.svg-image", "image/svg+xml", b"<svg", 1).is_ok());
+    }
+
+    #[test]
+    fn survives_truncated_headers() {
+        let tiff = tiff(false, 640, 480);
+
+        for length in 0..tiff.len() {
+            let _ = dimensions(&tiff[..length]);
+        }
+
+        assert_eq!(dimensions(b"II*\0\xff\xff\xff\xff"), None);
+    }
+}
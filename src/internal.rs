@@ -0,0 +1,281 @@
+//! Process-local clipboards.
+use crate::formats::{self, Format};
+use crate::{AccessState, Clipboard, ClipboardProvider, TEXT};
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError};
+
+/// The representations of the contents of a [`Board`], as pairs of format
+/// and data.
+type Representations = Vec<(Format, Vec<u8>)>;
+
+/// A clipboard that lives in the memory of the process.
+///
+/// A [`Board`] is a cheap handle: its clones share the same contents. It can
+/// be turned into a [`Clipboard`] to be used through the same API as the
+/// system clipboard.
+///
+/// Since it needs no window server, a [`Board`] can stand in for the system
+/// clipboard in tests: the test keeps a clone to inspect what the code
+/// under test copied, or to set what it pastes.
+#[derive(Debug, Clone, Default)]
+pub struct Board {
+    representations: Arc<Mutex<Representations>>,
+    changes: Arc<AtomicU64>,
+}
+
+impl Board {
+    /// Creates a new, empty [`Board`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the [`Board`] with the given name, creating it if needed.
+    ///
+    /// Every call with the same name in the process shares the same
+    /// contents.
+    pub fn named(name: &str) -> Self {
+        static BOARDS: OnceLock<Mutex<HashMap<String, Board>>> =
+            OnceLock::new();
+
+        BOARDS
+            .get_or_init(Mutex::default)
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(name.to_owned())
+            .or_default()
+            .clone()
+    }
+
+    /// Removes all the contents of the [`Board`].
+    pub fn clear(&self) {
+        self.replace(Vec::new());
+    }
+
+    /// Replaces the contents of the [`Board`], counting the change.
+    fn replace(&self, representations: Representations) {
+        let mut current = self.representations();
+
+        *current = representations;
+        let _ = self.changes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn representations(&self) -> MutexGuard<'_, Representations> {
+        self.representations
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn data(&self, format: &Format) -> Option<Vec<u8>> {
+        self.representations()
+            .iter()
+            .find(|(candidate, _)| candidate == format)
+            .map(|(_, data)| data.clone())
+    }
+}
+
+/// Where a register of a [`Router`] keeps its contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Route {
+    /// The system clipboard.
+    System,
+    /// The primary selection, or the system clipboard where there is none.
+    Primary,
+    /// The named [`Board`] of the register.
+    Memory,
+}
+
+/// Maps named registers to the system clipboard, the primary selection or
+/// process-local [`Board`]s.
+///
+/// Registers without a route of their own are kept in memory, unless a
+/// different default is set.
+pub struct Router {
+    system: Clipboard,
+    routes: HashMap<String, Route>,
+    default: Route,
+}
+
+impl Router {
+    /// Creates a [`Router`] over the given system [`Clipboard`].
+    pub fn new(system: Clipboard) -> Self {
+        Router {
+            system,
+            routes: HashMap::new(),
+            default: Route::Memory,
+        }
+    }
+
+    /// Routes the register with the given name.
+    pub fn route(mut self, register: &str, route: Route) -> Self {
+        let _ = self.routes.insert(register.to_owned(), route);
+        self
+    }
+
+    /// Sets the [`Route`] of the registers without one of their own.
+    pub fn default_route(mut self, route: Route) -> Self {
+        self.default = route;
+        self
+    }
+
+    /// Returns the [`Route`] of the register with the given name.
+    pub fn route_of(&self, register: &str) -> Route {
+        self.routes.get(register).copied().unwrap_or(self.default)
+    }
+
+    /// Reads the text of the register with the given name.
+    pub fn read(&self, register: &str) -> Result<String, crate::Error> {
+        match self.route_of(register) {
+            Route::System => self.system.read(),
+            Route::Primary => self
+                .system
+                .read_primary()
+                .unwrap_or_else(|| self.system.read()),
+            Route::Memory => Clipboard::from(Board::named(register)).read(),
+        }
+    }
+
+    /// Writes text to the register with the given name.
+    pub fn write(
+        &mut self,
+        register: &str,
+        contents: String,
+    ) -> Result<(), crate::Error> {
+        match self.route_of(register) {
+            Route::System => self.system.write(contents),
+            Route::Primary => match self.system.write_primary(contents.clone())
+            {
+                Some(result) => result,
+                None => self.system.write(contents),
+            },
+            Route::Memory => {
+                Clipboard::from(Board::named(register)).write(contents)
+            }
+        }
+    }
+}
+
+impl From<Board> for Clipboard {
+    fn from(board: Board) -> Self {
+        Clipboard::new(Box::new(board))
+    }
+}
+
+impl ClipboardProvider for Board {
+    fn read(&self) -> Result<String, Box<dyn std::error::Error>> {
+        let representations = self.representations();
+
+        let (_, data) = representations
+            .iter()
+            .find(|(format, _)| {
+                formats::normalize(format.name()) == Some("text/plain")
+            })
+            .ok_or(Error::Unavailable)?;
+
+        Ok(String::from_utf8(data.clone())?)
+    }
+
+    fn write(
+        &mut self,
+        contents: String,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.replace(vec![(Format::new(TEXT), contents.into())]);
+
+        Ok(())
+    }
+
+    fn read_format(
+        &self,
+        format: &Format,
+    ) -> Option<Result<Vec<u8>, Box<dyn std::error::Error>>> {
+        Some(self.data(format).ok_or_else(|| Error::Unavailable.into()))
+    }
+
+    fn read_html(&self) -> Option<Result<String, Box<dyn std::error::Error>>> {
+        let html = self.data(&Format::new("text/html"));
+
+        Some(match html {
+            Some(html) => String::from_utf8(html).map_err(Box::from),
+            None => Err(Box::new(Error::Unavailable)),
+        })
+    }
+
+    fn available_formats(
+        &self,
+    ) -> Option<Result<Vec<Format>, Box<dyn std::error::Error>>> {
+        Some(Ok(self
+            .representations()
+            .iter()
+            .map(|(format, _)| format.clone())
+            .collect()))
+    }
+
+    fn available_of(
+        &self,
+        formats: &[Format],
+    ) -> Option<Result<Vec<bool>, Box<dyn std::error::Error>>> {
+        let representations = self.representations();
+
+        Some(Ok(formats
+            .iter()
+            .map(|format| {
+                representations
+                    .iter()
+                    .any(|(candidate, _)| candidate == format)
+            })
+            .collect()))
+    }
+
+    fn format_size(&self, format: &Format) -> Option<u64> {
+        self.data(format).map(|data| data.len() as u64)
+    }
+
+    fn write_text_types(
+        &mut self,
+        contents: String,
+        formats: &[Format],
+    ) -> Option<Result<(), Box<dyn std::error::Error>>> {
+        self.replace(
+            formats
+                .iter()
+                .map(|format| (format.clone(), contents.clone().into_bytes()))
+                .collect(),
+        );
+
+        Some(Ok(()))
+    }
+
+    fn write_data(
+        &mut self,
+        items: &[(Format, Vec<u8>)],
+    ) -> Option<Result<(), Box<dyn std::error::Error>>> {
+        self.replace(items.to_vec());
+
+        Some(Ok(()))
+    }
+
+    fn clear(&mut self) -> Option<Result<(), Box<dyn std::error::Error>>> {
+        Board::clear(self);
+
+        Some(Ok(()))
+    }
+
+    fn change_count(&self) -> Option<Result<u64, Box<dyn std::error::Error>>> {
+        Some(Ok(self.changes.load(Ordering::Relaxed)))
+    }
+
+    fn check_access(&self) -> AccessState {
+        AccessState::Granted
+    }
+
+    fn backend(&self) -> &'static str {
+        "internal"
+    }
+}
+
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("format unavailable")]
+    Unavailable,
+}
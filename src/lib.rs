@@ -46,52 +46,1760 @@ mod platform;
 #[path = "platform/dummy.rs"]
 mod platform;
 
+pub mod content;
+pub mod description;
+pub mod expiry;
+pub mod files;
+pub mod formats;
+#[cfg(feature = "highlight")]
+pub mod highlight;
+pub mod image;
+pub mod internal;
+pub mod metrics;
+pub mod mime;
+pub mod mode;
+pub mod payload;
+pub mod profile;
+pub mod quarantine;
+pub mod table;
+pub mod text;
+
+mod error;
+
+pub use content::ClipboardContent;
+pub use error::{Context, Error, ErrorKind, Operation, PlatformCode};
+pub use files::FileList;
+pub use formats::{
+    ContentKind, Contents, Converter, Format, Item, Promise, Representations,
+};
+pub use metrics::{Metrics, OperationMetrics};
+pub use mode::Mode;
+pub use payload::PayloadLocation;
+pub use profile::Profile;
+pub use quarantine::Untrusted;
+pub use text::PastedText;
+
+#[cfg(feature = "normalization")]
+pub use text::Normalization;
+
+#[cfg(target_os = "windows")]
+pub use platform::pump;
+
+use expiry::{ClearTimer, Expired};
+use metrics::Recorder;
+use quarantine::{Quarantined, Written};
+
 use raw_window_handle::HasDisplayHandle;
-use std::error::Error;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime};
+
+/// The format name reported in the [`Context`] of text operations.
+const TEXT: &str = "text/plain;charset=utf-8";
+
+/// Text along with the available formats of the same contents.
+type TextWithFormats = (String, Vec<Format>);
 
 pub struct Clipboard {
     raw: Box<dyn ClipboardProvider>,
+    converters: Vec<(Format, Format, Converter)>,
+    metrics: Mutex<Recorder>,
+    /// The expiry of the contents, along with the change count it was read
+    /// at.
+    expiry: Mutex<Option<(u64, Option<SystemTime>)>>,
+    spill_threshold: Option<u64>,
+    quarantine: bool,
+    written: Written,
+    max_image_pixels: Option<u64>,
+    trim: bool,
+    mode: Mode,
+    #[cfg(feature = "normalization")]
+    normalization: Option<Normalization>,
 }
 
 impl Clipboard {
-    /// Safety: the display handle must be valid for the lifetime of `Clipboard`
+    /// Connect to the clipboard of the display that owns the given window.
+    ///
+    /// # Safety
+    /// The display handle must be valid for the lifetime of `Clipboard`.
     pub unsafe fn connect<W: HasDisplayHandle>(
         window: &W,
-    ) -> Result<Self, Box<dyn Error>> {
-        let raw = platform::connect(window)?;
+    ) -> Result<Self, Error> {
+        let raw = platform::connect(window).map_err(|error| {
+            Error::new(Operation::Connect, platform::BACKEND, None, error)
+        })?;
+
+        Ok(Clipboard::new(raw))
+    }
+
+    fn new(raw: Box<dyn ClipboardProvider>) -> Self {
+        Clipboard {
+            raw,
+            converters: Vec::new(),
+            metrics: Mutex::default(),
+            expiry: Mutex::default(),
+            spill_threshold: None,
+            quarantine: false,
+            written: Written::default(),
+            max_image_pixels: Some(image::DEFAULT_MAX_PIXELS),
+            trim: false,
+            mode: Mode::ReadWrite,
+            #[cfg(feature = "normalization")]
+            normalization: None,
+        }
+    }
+
+    pub fn read(&self) -> Result<String, Error> {
+        self.check_readable(Operation::Read)?;
+
+        let start = Recorder::start();
+        let result = self.raw.read();
+        self.record(Operation::Read, start, result.is_ok());
+
+        let text =
+            result.map_err(|error| self.error(Operation::Read, error))?;
+
+        if self
+            .current_expiry()
+            .is_some_and(|expiry| expiry <= SystemTime::now())
+        {
+            return Err(self.error(Operation::Read, Box::new(Expired)));
+        }
+
+        let text = self.admit(Operation::Read, text)?;
+        let text = self.normalize(text);
+
+        if self.trim {
+            Ok(text.trim().to_owned())
+        } else {
+            Ok(text)
+        }
+    }
+
+    /// Reads the text in the clipboard, telling apart empty and
+    /// whitespace-only text from text with content.
+    pub fn read_pasted(&self) -> Result<PastedText, Error> {
+        self.read().map(PastedText::new)
+    }
+
+    /// Sets the Unicode [`Normalization`] form of the text read and written
+    /// through [`read`](Self::read), [`write`](Self::write) and their primary
+    /// selection counterparts, or `None` to leave text as is.
+    ///
+    /// Text is left as is by default.
+    #[cfg(feature = "normalization")]
+    pub fn set_normalization(&mut self, normalization: Option<Normalization>) {
+        self.normalization = normalization;
+    }
+
+    #[cfg(feature = "normalization")]
+    fn normalize(&self, text: String) -> String {
+        match self.normalization {
+            Some(normalization) => normalization.apply(&text),
+            None => text,
+        }
+    }
+
+    #[cfg(not(feature = "normalization"))]
+    fn normalize(&self, text: String) -> String {
+        text
+    }
+
+    /// Sets whether [`read`](Self::read) trims leading and trailing
+    /// whitespace from the text.
+    ///
+    /// This is disabled by default.
+    pub fn set_trim_on_read(&mut self, trim: bool) {
+        self.trim = trim;
+    }
+
+    /// Returns the expiry written along with the current contents, if any.
+    pub fn expiry(&self) -> Option<SystemTime> {
+        self.check_readable(Operation::Read).ok()?;
+
+        let data = self.raw.read_format(&Format::new(expiry::FORMAT))?.ok()?;
+
+        expiry::decode(&data)
+    }
+
+    /// Returns the [`expiry`](Self::expiry) of the current contents, read
+    /// once per change count when the backend keeps one.
+    fn current_expiry(&self) -> Option<SystemTime> {
+        let Some(count) = self.raw.change_count().and_then(Result::ok) else {
+            return self.expiry();
+        };
+
+        let mut cached =
+            self.expiry.lock().unwrap_or_else(PoisonError::into_inner);
+
+        match *cached {
+            Some((cached_count, expiry)) if cached_count == count => expiry,
+            _ => {
+                let expiry = self.expiry();
+                *cached = Some((count, expiry));
+
+                expiry
+            }
+        }
+    }
+
+    /// Writes text to the clipboard along with an expiry time, stored as
+    /// the [`expiry::FORMAT`] metadata format.
+    ///
+    /// Once it has passed, [`read`](Self::read) fails with an [`Expired`]
+    /// source error. Other applications may honor the expiry as well.
+    /// Returns `None` if the backend does not support writing arbitrary
+    /// formats.
+    pub fn write_expiring<'a>(
+        &mut self,
+        contents: impl Into<Cow<'a, str>>,
+        expires_at: SystemTime,
+    ) -> Option<Result<(), Error>> {
+        self.write_text_with(
+            &contents.into(),
+            [(Format::new(expiry::FORMAT), expiry::encode(expires_at))],
+        )
+    }
+
+    /// Returns the accessibility description written along with the
+    /// current contents, if any.
+    pub fn description(&self) -> Option<String> {
+        self.check_readable(Operation::Read).ok()?;
+
+        let format = Format::new(description::FORMAT);
+        let data = self.raw.read_format(&format)?.ok()?;
+        let data = self.admit(Operation::Read, data).ok()?;
+
+        String::from_utf8(data).ok()
+    }
+
+    /// Writes each pair of [`Format`] and data to the clipboard along with
+    /// a short accessibility description, stored as the
+    /// [`description::FORMAT`] metadata format.
+    ///
+    /// Assistive tools can announce the description, like
+    /// `image: bar chart of Q3 sales`, instead of the type and size of the
+    /// data. Returns `None` if the backend does not support writing
+    /// arbitrary formats.
+    pub fn write_described(
+        &mut self,
+        items: &[(Format, Vec<u8>)],
+        description: &str,
+    ) -> Option<Result<(), Error>> {
+        let mut items = items.to_vec();
+        items.push((Format::new(description::FORMAT), description.into()));
+
+        self.write_data(&items)
+    }
+
+    /// Writes text to the clipboard marked with the given [`WriteOptions`],
+    /// so clipboard history tools that honor the markers of the platform
+    /// leave secrets out.
+    ///
+    /// macOS uses the `org.nspasteboard.TransientType` and
+    /// `org.nspasteboard.ConcealedType` conventions, Windows the
+    /// `CanIncludeInClipboardHistory`, `CanUploadToCloudClipboard` and
+    /// `ExcludeClipboardContentFromMonitorProcessing` formats, and X11 and
+    /// Wayland the `x-kde-passwordManagerHint` of concealed contents.
+    ///
+    /// Returns `None` if the backend does not support writing arbitrary
+    /// formats.
+    pub fn write_with_options<'a>(
+        &mut self,
+        contents: impl Into<Cow<'a, str>>,
+        options: &WriteOptions,
+    ) -> Option<Result<(), Error>> {
+        let markers = platform::write_markers(options);
+
+        self.write_text_with(&contents.into(), markers)
+    }
+
+    /// Sets whether contents written by other processes are quarantined.
+    ///
+    /// While enabled, every read of the contents fails with a
+    /// [`Quarantined`] source error unless they were written by this
+    /// [`Clipboard`] and not replaced since. Such text can only be obtained
+    /// through [`read_untrusted`](Self::read_untrusted).
+    ///
+    /// The origin of the contents is told by the owner of the selection or
+    /// the [change count](Self::change_count), not by what they hold.
+    /// Backends that can track neither quarantine all contents.
+    pub fn set_quarantine(&mut self, quarantine: bool) {
+        self.quarantine = quarantine;
+    }
+
+    /// Reads the text in the clipboard as [`Untrusted`], which must be
+    /// accepted before use.
+    pub fn read_untrusted(&self) -> Result<Untrusted, Error> {
+        self.check_readable(Operation::Read)?;
+
+        let start = Recorder::start();
+        let result = self.raw.read();
+        self.record(Operation::Read, start, result.is_ok());
+
+        let text =
+            result.map_err(|error| self.error(Operation::Read, error))?;
+        let local = self.is_local(Selection::Clipboard);
+
+        Ok(Untrusted::new(text, local))
+    }
+
+    /// Returns what the [`Clipboard`] is allowed to do.
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Restricts the [`Clipboard`] to reads, so every write fails with a
+    /// [`ReadOnly`](mode::ReadOnly) source error of
+    /// [`ErrorKind::Refused`].
+    ///
+    /// The handle is consumed, so code given the restricted one cannot
+    /// allow writes again. Restricting a write-only handle this way leaves
+    /// it with [`Mode::NoAccess`].
+    pub fn into_read_only(mut self) -> Self {
+        self.mode = self.mode.restrict(Mode::ReadOnly);
+        self
+    }
+
+    /// Restricts the [`Clipboard`] to writes, so every read fails with a
+    /// [`WriteOnly`](mode::WriteOnly) source error of
+    /// [`ErrorKind::Refused`].
+    ///
+    /// The handle is consumed, so code given the restricted one cannot
+    /// allow reads again. Restricting a read-only handle this way leaves it
+    /// with [`Mode::NoAccess`].
+    pub fn into_write_only(mut self) -> Self {
+        self.mode = self.mode.restrict(Mode::WriteOnly);
+        self
+    }
+
+    /// Fails if the [`Mode`] does not allow reads.
+    fn check_readable(&self, operation: Operation) -> Result<(), Error> {
+        if self.mode.can_read() {
+            return Ok(());
+        }
+
+        Err(Error::new(
+            operation,
+            self.raw.backend(),
+            None,
+            Box::new(mode::WriteOnly),
+        ))
+    }
+
+    /// Fails if the [`Mode`] does not allow writes.
+    fn check_writable(&self, operation: Operation) -> Result<(), Error> {
+        if self.mode.can_write() {
+            return Ok(());
+        }
+
+        Err(Error::new(
+            operation,
+            self.raw.backend(),
+            None,
+            Box::new(mode::ReadOnly),
+        ))
+    }
+
+    /// Hands `value` over unless it was read from quarantined contents.
+    ///
+    /// The origin of the contents is checked after the read, so contents
+    /// replaced in the meantime are never mistaken for local ones.
+    fn admit<T>(&self, operation: Operation, value: T) -> Result<T, Error> {
+        let selection = match operation {
+            Operation::ReadPrimary => Selection::Primary,
+            _ => Selection::Clipboard,
+        };
+
+        if self.quarantine && !self.is_local(selection) {
+            return Err(self.error(operation, Box::new(Quarantined)));
+        }
+
+        Ok(value)
+    }
+
+    /// Returns whether the given [`Selection`] still holds what this
+    /// [`Clipboard`] wrote last.
+    ///
+    /// Backends that know the owner of a selection are asked directly.
+    /// Otherwise, the clipboard is local while its change count has not
+    /// moved since the last write. Contents whose origin cannot be told
+    /// are never local.
+    fn is_local(&self, selection: Selection) -> bool {
+        if let Some(is_owner) = self.raw.is_owner(selection) {
+            return is_owner.unwrap_or(false);
+        }
+
+        match selection {
+            Selection::Clipboard => {
+                let count = self.raw.change_count().and_then(Result::ok);
+
+                count.is_some() && count == self.written.clipboard
+            }
+            Selection::Primary => false,
+        }
+    }
+
+    /// Records the change count right after a write to the clipboard.
+    fn mark_written(&mut self) {
+        self.written.clipboard = self.raw.change_count().and_then(Result::ok);
+    }
+
+    /// Reads the text in the clipboard along with its available
+    /// [`Format`]s, both from the same copy of the contents.
+    ///
+    /// Returns `None` if the backend cannot read both consistently.
+    pub fn read_with_formats(
+        &self,
+    ) -> Option<Result<(String, Vec<Format>), Error>> {
+        if let Err(error) = self.check_readable(Operation::Read) {
+            return Some(Err(error));
+        }
+
+        let start = Recorder::start();
+        let result = self.raw.read_with_formats()?;
+        self.record(Operation::Read, start, result.is_ok());
+
+        Some(
+            result
+                .map_err(|error| self.error(Operation::Read, error))
+                .and_then(|read| self.admit(Operation::Read, read)),
+        )
+    }
+
+    /// Reads at most `max_bytes` of the text in the clipboard, shortened as
+    /// [`text::preview`] does.
+    ///
+    /// Backends that transfer data in chunks stop once enough of it has
+    /// arrived; others read the whole text first.
+    pub fn read_text_preview(&self, max_bytes: usize) -> Result<String, Error> {
+        self.check_readable(Operation::Read)?;
+
+        // One more character tells whether the last grapheme cluster is
+        // complete.
+        let start = Recorder::start();
+        let result = self.raw.read_text_preview(max_bytes.saturating_add(4));
+        self.record(Operation::Read, start, result.is_ok());
 
-        Ok(Clipboard { raw })
+        result
+            .map(|text| text::preview(&text, max_bytes).into_owned())
+            .map_err(|error| self.error(Operation::Read, error))
+            .and_then(|text| self.admit(Operation::Read, text))
     }
 
-    pub fn read(&self) -> Result<String, Box<dyn Error>> {
-        self.raw.read()
+    /// Writes text to the clipboard.
+    ///
+    /// Owned [`String`]s are handed to the backend without being copied.
+    pub fn write<'a>(
+        &mut self,
+        contents: impl Into<Cow<'a, str>>,
+    ) -> Result<(), Error> {
+        self.check_writable(Operation::Write)?;
+
+        let contents = self.normalize(contents.into().into_owned());
+
+        let start = Recorder::start();
+        let result = self.raw.write(contents);
+        self.record(Operation::Write, start, result.is_ok());
+
+        result.map_err(|error| self.error(Operation::Write, error))?;
+        self.mark_written();
+
+        Ok(())
+    }
+
+    /// Writes text to the clipboard, and clears it once `ttl` has elapsed
+    /// unless its contents have changed since, like password managers do.
+    ///
+    /// The clipboard is cleared from a background thread, checking the
+    /// change count of the platform, or on X11 the ownership of the
+    /// selection, so a later copy by the user is never wiped. The previous contents are not restored,
+    /// since they could be stale by then.
+    ///
+    /// The returned [`ClearTimer`] can cancel the clear. Dropping it leaves
+    /// the timer running.
+    ///
+    /// Returns `None` if the backend cannot clear the clipboard on its own.
+    pub fn write_with_ttl<'a>(
+        &mut self,
+        contents: impl Into<Cow<'a, str>>,
+        ttl: Duration,
+    ) -> Option<Result<ClearTimer, Error>> {
+        if let Err(error) = self.check_writable(Operation::Write) {
+            return Some(Err(error));
+        }
+
+        let contents = self.normalize(contents.into().into_owned());
+
+        let start = Recorder::start();
+        let result = self.raw.write_with_ttl(contents, ttl)?;
+        self.record(Operation::Write, start, result.is_ok());
+
+        if result.is_ok() {
+            self.mark_written();
+        }
+
+        Some(result.map_err(|error| self.error(Operation::Write, error)))
+    }
+
+    /// Writes UTF-8 text held in [`Bytes`](bytes::Bytes) to the clipboard.
+    ///
+    /// The buffer is reused without copying when it is not shared.
+    #[cfg(feature = "bytes")]
+    pub fn write_bytes(&mut self, contents: bytes::Bytes) -> Result<(), Error> {
+        let contents = String::from_utf8(Vec::from(contents))
+            .map_err(|error| self.error(Operation::Write, Box::new(error)))?;
+
+        self.write(contents)
+    }
+
+    /// Writes the concatenation of `slices` to the clipboard, as
+    /// [`write`](Self::write) does.
+    ///
+    /// The slices are joined into a single allocation of the exact total
+    /// length, so text kept in pieces, like a rope, does not need to be
+    /// collected into a growing [`String`] first.
+    pub fn write_text_slices(&mut self, slices: &[&str]) -> Result<(), Error> {
+        self.write(slices.concat())
+    }
+
+    /// Reads the data of the given [`Format`].
+    ///
+    /// Returns `None` if the backend does not support reading arbitrary
+    /// formats.
+    pub fn read_format(
+        &self,
+        format: &Format,
+    ) -> Option<Result<Vec<u8>, Error>> {
+        if let Err(error) = self.check_readable(Operation::Read) {
+            return Some(Err(error));
+        }
+
+        let start = Recorder::start();
+        let result = self.raw.read_format(format)?;
+        self.record(Operation::Read, start, result.is_ok());
+
+        Some(
+            result
+                .and_then(|data| {
+                    self.check_image(format, &data)?;
+
+                    Ok(data)
+                })
+                .map_err(|error| self.read_error(format, error))
+                .and_then(|data| self.admit(Operation::Read, data)),
+        )
+    }
+
+    /// Sets the maximum number of pixels an image read from the clipboard
+    /// may declare, or `None` to allow any size.
+    ///
+    /// Reads of image formats check the dimensions in the image header and
+    /// fail with an [`image::TooLarge`] source error if they exceed it, so
+    /// the data never reaches a decoder. Raster images whose header cannot
+    /// be read fail with an [`image::UnreadableHeader`] source error. Payloads that
+    /// [`read_payload`](Self::read_payload) writes straight to a file are
+    /// not checked.
+    ///
+    /// The limit defaults to [`image::DEFAULT_MAX_PIXELS`].
+    pub fn set_max_image_pixels(&mut self, max_pixels: Option<u64>) {
+        self.max_image_pixels = max_pixels;
+    }
+
+    /// Writes text in its native representation along with other ones.
+    fn write_text_with(
+        &mut self,
+        contents: &str,
+        representations: impl IntoIterator<Item = (Format, Vec<u8>)>,
+    ) -> Option<Result<(), Error>> {
+        let mut items = vec![platform::text_data(contents)];
+        items.extend(representations);
+
+        self.write_data(&items)
+    }
+
+    fn check_image(
+        &self,
+        format: &Format,
+        data: &[u8],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mime_type = formats::mime_type(format.name())
+            .filter(|mime_type| mime_type.starts_with("image/"));
+
+        match (self.max_image_pixels, mime_type) {
+            (Some(max_pixels), Some(mime_type)) => {
+                image::check(format.name(), mime_type, data, max_pixels)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Reads the data of every available [`Format`] of the current
+    /// contents.
+    ///
+    /// Formats whose data cannot be read are left out. Returns `None` if the
+    /// backend cannot list or read arbitrary formats.
+    pub fn read_all(&self) -> Option<Result<Representations, Error>> {
+        if let Err(error) = self.check_readable(Operation::Read) {
+            return Some(Err(error));
+        }
+
+        let start = Recorder::start();
+        let result = self.raw.read_all()?.and_then(|representations| {
+            for (format, data) in &representations {
+                self.check_image(format, data)?;
+            }
+
+            Ok(representations)
+        });
+        self.record(Operation::Read, start, result.is_ok());
+
+        Some(
+            result
+                .map_err(|error| {
+                    Error::new(Operation::Read, self.raw.backend(), None, error)
+                })
+                .and_then(|read| self.admit(Operation::Read, read)),
+        )
+    }
+
+    /// Reads every item of the clipboard, each with the data of all of its
+    /// [`Format`]s, so multi-file or multi-entry copies are not truncated
+    /// to their first item.
+    ///
+    /// Returns `None` if the backend does not support multiple items.
+    pub fn read_items(&self) -> Option<Result<Vec<Item>, Error>> {
+        if let Err(error) = self.check_readable(Operation::Read) {
+            return Some(Err(error));
+        }
+
+        let start = Recorder::start();
+        let result = self.raw.read_items()?.and_then(|items| {
+            for (format, data) in items.iter().flatten() {
+                self.check_image(format, data)?;
+            }
+
+            Ok(items)
+        });
+        self.record(Operation::Read, start, result.is_ok());
+
+        Some(
+            result
+                .map_err(|error| {
+                    Error::new(Operation::Read, self.raw.backend(), None, error)
+                })
+                .and_then(|read| self.admit(Operation::Read, read)),
+        )
+    }
+
+    /// Writes each [`Item`] to the clipboard as an item of its own,
+    /// replacing the contents.
+    ///
+    /// Returns `None` if the backend does not support multiple items.
+    pub fn write_items(&mut self, items: &[Item]) -> Option<Result<(), Error>> {
+        if let Err(error) = self.check_writable(Operation::Write) {
+            return Some(Err(error));
+        }
+
+        let start = Recorder::start();
+        let result = self.raw.write_items(items)?;
+        self.record(Operation::Write, start, result.is_ok());
+
+        if result.is_ok() {
+            self.mark_written();
+        }
+
+        Some(result.map_err(|error| {
+            Error::new(Operation::Write, self.raw.backend(), None, error)
+        }))
+    }
+
+    /// Reads the HTML in the clipboard.
+    ///
+    /// Returns `None` if the backend does not support reading HTML.
+    pub fn read_html(&self) -> Option<Result<String, Error>> {
+        if let Err(error) = self.check_readable(Operation::Read) {
+            return Some(Err(error));
+        }
+
+        let start = Recorder::start();
+        let result = self.raw.read_html()?;
+        self.record(Operation::Read, start, result.is_ok());
+
+        Some(
+            result
+                .map_err(|error| {
+                    Error::new(
+                        Operation::Read,
+                        self.raw.backend(),
+                        Some(String::from("text/html")),
+                        error,
+                    )
+                })
+                .and_then(|html| self.admit(Operation::Read, html)),
+        )
+    }
+
+    /// Reads the RTF document in the clipboard.
+    ///
+    /// Returns `None` if the backend does not support reading arbitrary
+    /// formats.
+    pub fn read_rtf(&self) -> Option<Result<Vec<u8>, Error>> {
+        self.read_format(&Format::new(platform::RTF))
+    }
+
+    /// Writes styled text to the clipboard as an RTF document, along with
+    /// its plain text for applications without RTF support.
+    ///
+    /// Returns `None` if the backend does not support writing arbitrary
+    /// formats.
+    pub fn write_rtf<'a>(
+        &mut self,
+        contents: impl Into<Cow<'a, str>>,
+        rtf: Vec<u8>,
+    ) -> Option<Result<(), Error>> {
+        let rtf = (Format::new(platform::RTF), rtf);
+
+        self.write_text_with(&contents.into(), [rtf])
+    }
+
+    /// Reads a table copied from a spreadsheet, as rows of cells.
+    ///
+    /// The CSV format is preferred, followed by Excel's
+    /// [`table::XML_SPREADSHEET`]. Otherwise, the text is parsed as
+    /// tab-separated values, which is how spreadsheets copy cells as text.
+    ///
+    /// Returns `None` if the backend does not support reading arbitrary
+    /// formats.
+    pub fn read_table(&self) -> Option<Result<Vec<Vec<String>>, Error>> {
+        if let Ok(csv) = self.read_format(&Format::new(platform::CSV))? {
+            let csv = String::from_utf8_lossy(&csv);
+
+            return Some(Ok(table::parse_csv(csv.trim_end_matches('\0'))));
+        }
+
+        let xml = Format::new(table::XML_SPREADSHEET);
+
+        if let Ok(xml) = self.read_format(&xml)? {
+            let xml = String::from_utf8_lossy(&xml);
+
+            return Some(Ok(table::parse_xml_spreadsheet(&xml)));
+        }
+
+        Some(self.read().map(|text| table::parse_tsv(&text)))
+    }
+
+    /// Writes a table to the clipboard as tab-separated text along with
+    /// CSV, so spreadsheets paste it as cells.
+    ///
+    /// On Windows, the table is offered as [`table::XML_SPREADSHEET`] too,
+    /// which Excel prefers.
+    ///
+    /// Returns `None` if the backend does not support writing arbitrary
+    /// formats.
+    pub fn write_table(
+        &mut self,
+        rows: &[Vec<String>],
+    ) -> Option<Result<(), Error>> {
+        let csv = table::to_csv(rows).into_bytes();
+        let mut representations = vec![(Format::new(platform::CSV), csv)];
+
+        if cfg!(target_os = "windows") {
+            representations.push((
+                Format::new(table::XML_SPREADSHEET),
+                table::to_xml_spreadsheet(rows).into_bytes(),
+            ));
+        }
+
+        self.write_text_with(&table::to_tsv(rows), representations)
+    }
+
+    /// Writes [`ClipboardContent`] to the clipboard, offering all of its
+    /// representations at once.
+    ///
+    /// Returns `None` if the backend does not support writing arbitrary
+    /// formats.
+    pub fn write_content(
+        &mut self,
+        content: &ClipboardContent,
+    ) -> Option<Result<(), Error>> {
+        let representations = content.representations.iter().cloned();
+
+        match &content.text {
+            Some(text) => self.write_text_with(text, representations),
+            None => self.write_data(&content.representations),
+        }
     }
 
-    pub fn write(&mut self, contents: String) -> Result<(), Box<dyn Error>> {
-        self.raw.write(contents)
+    /// Writes a MIME bundle to the clipboard, like the outputs of Jupyter
+    /// frontends, as pairs of MIME type and data.
+    ///
+    /// `text/plain`, `text/html` and `image/png` are written in their
+    /// native representations. Other MIME types are written under their
+    /// native name if known, converting the data if needed (see
+    /// [`mime::to_native_data`]). Images are given as raw bytes, not base64
+    /// like in notebook files.
+    ///
+    /// Returns `None` if the backend does not support writing arbitrary
+    /// formats.
+    pub fn write_mime_bundle(
+        &mut self,
+        bundle: &[(&str, &[u8])],
+    ) -> Option<Result<(), Error>> {
+        let mut text = None;
+        let mut items = Vec::with_capacity(bundle.len());
+
+        for &(mime, data) in bundle {
+            match mime {
+                "text/plain" => text = Some(String::from_utf8_lossy(data)),
+                "text/html" => {
+                    let html = String::from_utf8_lossy(data);
+
+                    items.push(platform::html_data(&html));
+                }
+                "image/png" => {
+                    items.push((Format::new(platform::PNG), data.to_vec()));
+                }
+                mime => items.push(mime::to_native_data(mime, data)),
+            }
+        }
+
+        match text {
+            Some(text) => self.write_text_with(&text, items),
+            None => self.write_data(&items),
+        }
+    }
+
+    /// Reads the MathML of an equation in the clipboard.
+    ///
+    /// Returns `None` if the backend does not support reading arbitrary
+    /// formats.
+    pub fn read_mathml(&self) -> Option<Result<String, Error>> {
+        let format = Format::new(platform::MATHML);
+
+        Some(self.read_format(&format)?.and_then(|data| {
+            String::from_utf8(data)
+                .map_err(|error| self.read_error(&format, Box::new(error)))
+        }))
+    }
+
+    /// Writes an equation to the clipboard as its LaTeX source along with
+    /// MathML, so word processors paste it as an equation while plain text
+    /// editors get the source.
+    ///
+    /// Returns `None` if the backend does not support writing arbitrary
+    /// formats.
+    pub fn write_math(
+        &mut self,
+        latex: &str,
+        mathml: &str,
+    ) -> Option<Result<(), Error>> {
+        let mathml = (Format::new(platform::MATHML), mathml.into());
+
+        self.write_text_with(latex, [mathml])
+    }
+
+    /// Writes source code to the clipboard as plain text along with
+    /// syntax-highlighted HTML and RTF, so rich text editors paste it in
+    /// color.
+    ///
+    /// The language is a name, like `Rust`, or a file extension, like `rs`.
+    /// Code of an unknown language is written without highlighting.
+    ///
+    /// Returns `None` if the backend does not support writing arbitrary
+    /// formats.
+    #[cfg(feature = "highlight")]
+    pub fn write_code(
+        &mut self,
+        code: &str,
+        language: &str,
+    ) -> Option<Result<(), Error>> {
+        let highlighted = match highlight::highlight(
+            code,
+            language,
+            highlight::DEFAULT_THEME,
+        ) {
+            Ok(highlighted) => highlighted,
+            Err(error) => {
+                return Some(Err(self.error(Operation::Write, error.into())))
+            }
+        };
+
+        self.write_text_with(
+            code,
+            [
+                platform::html_data(&highlighted.html),
+                (Format::new(platform::RTF), highlighted.rtf.into_bytes()),
+            ],
+        )
+    }
+
+    /// Reads the HTML in the clipboard, keeping only an allowlist of safe
+    /// markup.
+    ///
+    /// Scripts, styles, event handlers and images are removed, so the
+    /// result can be rendered without running code or loading external
+    /// resources.
+    ///
+    /// Returns `None` if the backend does not support reading HTML.
+    #[cfg(feature = "html")]
+    pub fn read_html_sanitized(&self) -> Option<Result<String, Error>> {
+        let result = self.read_html()?;
+
+        Some(result.map(|html| {
+            ammonia::Builder::default()
+                .rm_tags(["img"])
+                .clean(&html)
+                .to_string()
+        }))
+    }
+
+    /// Reads the file list in the clipboard, keeping only the local files.
+    ///
+    /// Backends with a native file list, like macOS, return its paths.
+    /// Others parse a `text/uri-list`, where entries that are not local
+    /// `file` URLs are reported in [`FileList::rejected`] instead of failing
+    /// the read.
+    ///
+    /// Returns `None` if the backend does not support reading file lists or
+    /// arbitrary formats.
+    pub fn read_files(&self) -> Option<Result<FileList, Error>> {
+        if let Err(error) = self.check_readable(Operation::Read) {
+            return Some(Err(error));
+        }
+
+        let format = Format::new("text/uri-list");
+
+        if let Some(result) = self.raw.read_files() {
+            return Some(
+                result
+                    .map(|paths| FileList {
+                        paths,
+                        rejected: Vec::new(),
+                    })
+                    .map_err(|error| self.read_error(&format, error))
+                    .and_then(|files| self.admit(Operation::Read, files)),
+            );
+        }
+
+        Some(self.read_format(&format)?.and_then(|data| {
+            let list = String::from_utf8(data)
+                .map_err(|error| self.read_error(&format, Box::new(error)))?;
+
+            Ok(files::parse_uri_list(&list))
+        }))
+    }
+
+    /// Writes a list of local files to the clipboard, so file managers can
+    /// paste them.
+    ///
+    /// Backends without a native file list write a `text/uri-list`.
+    /// Returns `None` if the backend does not support either.
+    pub fn write_files(
+        &mut self,
+        paths: &[PathBuf],
+    ) -> Option<Result<(), Error>> {
+        if let Err(error) = self.check_writable(Operation::Write) {
+            return Some(Err(error));
+        }
+
+        let start = Recorder::start();
+        let result = match self.raw.write_files(paths) {
+            Some(result) => result,
+            None => self.raw.write_data(&[(
+                Format::new("text/uri-list"),
+                files::to_uri_list(paths).into_bytes(),
+            )])?,
+        };
+        self.record(Operation::Write, start, result.is_ok());
+
+        if result.is_ok() {
+            self.mark_written();
+        }
+
+        Some(result.map_err(|error| {
+            Error::new(
+                Operation::Write,
+                self.raw.backend(),
+                Some("text/uri-list".to_owned()),
+                error,
+            )
+        }))
+    }
+
+    /// Sets the size in bytes above which
+    /// [`read_payload`](Self::read_payload) spills data to a temporary file.
+    ///
+    /// Spilling is disabled by default.
+    pub fn set_spill_threshold(&mut self, threshold: Option<u64>) {
+        self.spill_threshold = threshold;
+    }
+
+    /// Reads the data of the given [`Format`], spilling it to a temporary
+    /// file if it exceeds the spill threshold.
+    ///
+    /// Backends that can tell the size of a [`Format`] up front write large
+    /// payloads straight to the file; others read them in memory first.
+    ///
+    /// Returns `None` if the backend does not support reading arbitrary
+    /// formats.
+    pub fn read_payload(
+        &self,
+        format: &Format,
+    ) -> Option<Result<PayloadLocation, Error>> {
+        let threshold = match self.spill_threshold {
+            Some(threshold) => threshold,
+            None => {
+                return Some(
+                    self.read_format(format)?.map(PayloadLocation::Memory),
+                )
+            }
+        };
+
+        if self
+            .format_size(format)
+            .is_some_and(|size| size > threshold)
+        {
+            let start = Recorder::start();
+            let result =
+                payload::spill(|file| self.raw.read_format_to(format, file))?;
+            self.record(Operation::Read, start, result.is_ok());
+
+            let result = result.map_err(|error| self.read_error(format, error));
+
+            if let Ok(PayloadLocation::File(path)) = &result {
+                if let Err(error) = self.admit(Operation::Read, ()) {
+                    let _ = std::fs::remove_file(path);
+
+                    return Some(Err(error));
+                }
+            }
+
+            return Some(result);
+        }
+
+        let data = match self.read_format(format)? {
+            Ok(data) => data,
+            Err(error) => return Some(Err(error)),
+        };
+
+        if data.len() as u64 <= threshold {
+            return Some(Ok(PayloadLocation::Memory(data)));
+        }
+
+        let result = payload::spill(|file| {
+            Some(
+                file.write_all(&data)
+                    .map(|_| data.len() as u64)
+                    .map_err(Box::from),
+            )
+        })?;
+
+        Some(result.map_err(|error| self.read_error(format, error)))
+    }
+
+    /// Returns the [`Format`]s of the current contents, in the order the
+    /// platform lists them, without transferring any data.
+    ///
+    /// Each [`Format`] keeps its platform-native name, like a UTI on macOS
+    /// or a clipboard format name on Windows, and can be
+    /// [normalized](Format::normalized) to a MIME type. This is what a
+    /// "Paste as…" menu can be built from.
+    ///
+    /// Returns `None` if the backend cannot list the available formats.
+    pub fn available_formats(&self) -> Option<Result<Vec<Format>, Error>> {
+        if let Err(error) = self.check_readable(Operation::Read) {
+            return Some(Err(error));
+        }
+
+        let result = self.raw.available_formats()?;
+
+        Some(result.map_err(|error| {
+            Error::new(Operation::Read, self.raw.backend(), None, error)
+        }))
+    }
+
+    /// Returns whether each of the given [`Format`]s is available, in a
+    /// single query to the platform.
+    ///
+    /// Returns `None` if the backend cannot list the available formats.
+    pub fn available_of(
+        &self,
+        formats: &[Format],
+    ) -> Option<Result<Vec<bool>, Error>> {
+        if let Err(error) = self.check_readable(Operation::Read) {
+            return Some(Err(error));
+        }
+
+        let result = self.raw.available_of(formats)?;
+
+        Some(result.map_err(|error| {
+            Error::new(Operation::Read, self.raw.backend(), None, error)
+        }))
+    }
+
+    /// Classifies the contents of the clipboard by their declared formats,
+    /// without transferring any data.
+    ///
+    /// Returns `None` if the backend cannot list the available formats.
+    pub fn classify(&self) -> Option<Result<ContentKind, Error>> {
+        if let Err(error) = self.check_readable(Operation::Read) {
+            return Some(Err(error));
+        }
+
+        let result = self.raw.available_formats()?;
+
+        Some(
+            result
+                .map(|formats| ContentKind::of(&formats))
+                .map_err(|error| {
+                    Error::new(Operation::Read, self.raw.backend(), None, error)
+                }),
+        )
+    }
+
+    /// Returns the size in bytes of the data of the given [`Format`], without
+    /// transferring it.
+    ///
+    /// Returns `None` if the [`Format`] is unavailable or the backend cannot
+    /// tell its size.
+    pub fn format_size(&self, format: &Format) -> Option<u64> {
+        self.check_readable(Operation::Read).ok()?;
+
+        self.raw.format_size(format)
+    }
+
+    /// Reads the most preferred [`Format`] available according to the given
+    /// [`Profile`], returning it along with its data.
+    ///
+    /// With the `log` feature, every other available [`Format`] is logged
+    /// at debug level along with its size, when known.
+    ///
+    /// Returns `None` if no acceptable [`Format`] is available, or if the
+    /// backend cannot list or read arbitrary formats.
+    pub fn read_preferred(
+        &self,
+        profile: &Profile,
+    ) -> Option<Result<(Format, Vec<u8>), Error>> {
+        if let Err(error) = self.check_readable(Operation::Read) {
+            return Some(Err(error));
+        }
+
+        let formats = match self.raw.available_formats()? {
+            Ok(formats) => formats,
+            Err(error) => {
+                return Some(Err(Error::new(
+                    Operation::Read,
+                    self.raw.backend(),
+                    None,
+                    error,
+                )))
+            }
+        };
+
+        let format = profile.rank(&formats).into_iter().next()?;
+        let result = self.read_format(&format)?;
+
+        #[cfg(feature = "log")]
+        for ignored in formats.iter().filter(|&other| *other != format) {
+            match self.format_size(ignored) {
+                Some(size) => log::debug!(
+                    "read {} and ignored {} ({} bytes)",
+                    format,
+                    ignored,
+                    size
+                ),
+                None => log::debug!("read {} and ignored {}", format, ignored),
+            }
+        }
+
+        Some(result.map(|data| (format, data)))
+    }
+
+    /// Reads the data of the given [`Format`], converting it with a
+    /// registered [`Converter`] from another [`Format`] if it is unavailable.
+    ///
+    /// Returns `None` if the backend does not support reading arbitrary
+    /// formats.
+    pub fn read_format_converted(
+        &self,
+        format: &Format,
+    ) -> Option<Result<Contents, Error>> {
+        let error = match self.read_format(format)? {
+            Ok(data) => {
+                return Some(Ok(Contents {
+                    data,
+                    converted_from: None,
+                }))
+            }
+            Err(error) => error,
+        };
+
+        let converted = self
+            .converters
+            .iter()
+            .filter(|(_, to, _)| to == format)
+            .find_map(|(from, _, converter)| {
+                let data = self.read_format(from)?.ok()?;
+
+                Some(Contents {
+                    data: converter(&data)?,
+                    converted_from: Some(from.clone()),
+                })
+            });
+
+        Some(converted.ok_or(error))
+    }
+
+    /// Writes `contents` to the clipboard, declaring it only as the given
+    /// textual [`Format`]s.
+    ///
+    /// Returns `None` if the backend does not support choosing the formats.
+    pub fn write_text_types<'a>(
+        &mut self,
+        contents: impl Into<Cow<'a, str>>,
+        formats: &[Format],
+    ) -> Option<Result<(), Error>> {
+        if let Err(error) = self.check_writable(Operation::Write) {
+            return Some(Err(error));
+        }
+
+        let contents = contents.into().into_owned();
+
+        let start = Recorder::start();
+        let result = self.raw.write_text_types(contents, formats)?;
+        self.record(Operation::Write, start, result.is_ok());
+
+        if result.is_ok() {
+            self.mark_written();
+        }
+
+        Some(result.map_err(|error| {
+            let formats: Vec<_> = formats.iter().map(Format::name).collect();
+
+            Error::new(
+                Operation::Write,
+                self.raw.backend(),
+                Some(formats.join(", ")),
+                error,
+            )
+        }))
+    }
+
+    /// Writes each pair of [`Format`] and data to the clipboard, declaring
+    /// only those formats.
+    ///
+    /// The data is written as is, so custom formats can be offered next to
+    /// the standard ones. Returns `None` if the backend does not support
+    /// writing arbitrary formats.
+    pub fn write_data(
+        &mut self,
+        items: &[(Format, Vec<u8>)],
+    ) -> Option<Result<(), Error>> {
+        if let Err(error) = self.check_writable(Operation::Write) {
+            return Some(Err(error));
+        }
+
+        let start = Recorder::start();
+        let result = self.raw.write_data(items)?;
+        self.record(Operation::Write, start, result.is_ok());
+
+        if result.is_ok() {
+            self.mark_written();
+        }
+
+        Some(result.map_err(|error| {
+            let formats: Vec<_> =
+                items.iter().map(|(format, _)| format.name()).collect();
+
+            Error::new(
+                Operation::Write,
+                self.raw.backend(),
+                Some(formats.join(", ")),
+                error,
+            )
+        }))
+    }
+
+    /// Returns a number that changes whenever the contents of the clipboard
+    /// may have changed, so callers can cheaply tell whether to read them
+    /// again.
+    ///
+    /// It is the `changeCount` of the pasteboard on macOS, the clipboard
+    /// sequence number on Windows, and the number of owner changes seen
+    /// through XFIXES on X11. Only equality between two counts is
+    /// meaningful.
+    ///
+    /// Returns `None` if the backend cannot track changes.
+    pub fn change_count(&self) -> Option<Result<u64, Error>> {
+        let result = self.raw.change_count()?;
+
+        Some(result.map_err(|error| {
+            Error::new(Operation::Read, self.raw.backend(), None, error)
+        }))
+    }
+
+    /// Empties the clipboard without writing anything, so no empty text is
+    /// left behind, like password managers need after a timeout.
+    ///
+    /// Returns `None` if the backend cannot empty the clipboard.
+    pub fn clear(&mut self) -> Option<Result<(), Error>> {
+        if let Err(error) = self.check_writable(Operation::Write) {
+            return Some(Err(error));
+        }
+
+        let start = Recorder::start();
+        let result = self.raw.clear()?;
+        self.record(Operation::Write, start, result.is_ok());
+
+        if result.is_ok() {
+            self.written.clipboard = None;
+        }
+
+        Some(result.map_err(|error| {
+            Error::new(Operation::Write, self.raw.backend(), None, error)
+        }))
+    }
+
+    /// Writes each pair of [`Format`] and data to the clipboard, along with
+    /// [`Format`]s whose data is only produced by a [`Promise`] once a paste
+    /// requests them.
+    ///
+    /// This avoids encoding large images or files on every copy. A
+    /// [`Promise`] must not use the clipboard; backends fail such
+    /// operations instead of hanging.
+    ///
+    /// Returns `None` if the backend does not support promised data.
+    pub fn write_promised(
+        &mut self,
+        items: &[(Format, Vec<u8>)],
+        promises: &[(Format, Promise)],
+    ) -> Option<Result<(), Error>> {
+        if let Err(error) = self.check_writable(Operation::Write) {
+            return Some(Err(error));
+        }
+
+        let start = Recorder::start();
+        let result = self.raw.write_promised(items, promises)?;
+        self.record(Operation::Write, start, result.is_ok());
+
+        if result.is_ok() {
+            self.mark_written();
+        }
+
+        Some(result.map_err(|error| {
+            let formats: Vec<_> = items
+                .iter()
+                .map(|(format, _)| format)
+                .chain(promises.iter().map(|(format, _)| format))
+                .map(Format::name)
+                .collect();
+
+            Error::new(
+                Operation::Write,
+                self.raw.backend(),
+                Some(formats.join(", ")),
+                error,
+            )
+        }))
+    }
+
+    /// Registers a [`Converter`] that synthesizes the `to` [`Format`] from
+    /// the `from` [`Format`] of the contents written afterwards.
+    ///
+    /// The conversion only runs when a paste target requests `to` and the
+    /// written contents include `from` but not `to`. The [`Converter`] is
+    /// also used by [`read_format_converted`](Self::read_format_converted).
+    ///
+    /// A [`Converter`] must not use the clipboard while serving a paste;
+    /// backends fail such operations instead of deadlocking.
+    ///
+    /// Returns `false` if the backend does not support converters on write.
+    pub fn register_converter(
+        &mut self,
+        from: &Format,
+        to: &Format,
+        converter: Converter,
+    ) -> bool {
+        self.converters.push((
+            from.clone(),
+            to.clone(),
+            Arc::clone(&converter),
+        ));
+
+        self.raw.register_converter(from, to, converter)
+    }
+
+    /// Sets how long reads wait for the data of the given [`Format`], or
+    /// of any [`Format`] without a timeout of its own if `None`.
+    ///
+    /// Slow formats, like large images, can be given longer than text
+    /// without making every read wait as long.
+    ///
+    /// Returns `false` if the backend does not support read timeouts.
+    pub fn set_read_timeout(
+        &mut self,
+        format: Option<&Format>,
+        timeout: Duration,
+    ) -> bool {
+        self.raw.set_read_timeout(format, timeout)
+    }
+
+    /// Returns the [`Metrics`] of the operations performed so far.
+    ///
+    /// Reads and writes of specific formats count as [`Operation::Read`] and
+    /// [`Operation::Write`].
+    pub fn metrics(&self) -> Metrics {
+        self.metrics
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .snapshot(self.raw.backend())
+    }
+
+    fn record(
+        &self,
+        operation: Operation,
+        start: Option<Instant>,
+        success: bool,
+    ) {
+        self.metrics
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .record(operation, start, success);
+    }
+
+    fn read_error(
+        &self,
+        format: &Format,
+        error: Box<dyn std::error::Error>,
+    ) -> Error {
+        Error::new(
+            Operation::Read,
+            self.raw.backend(),
+            Some(format.name().to_owned()),
+            error,
+        )
+    }
+
+    fn error(
+        &self,
+        operation: Operation,
+        error: Box<dyn std::error::Error>,
+    ) -> Error {
+        Error::new(operation, self.raw.backend(), Some(TEXT.to_owned()), error)
     }
 }
 
 impl Clipboard {
-    pub fn read_primary(&self) -> Option<Result<String, Box<dyn Error>>> {
-        self.raw.read_primary()
+    /// Checks whether the application is allowed to read the clipboard,
+    /// without attempting a read.
+    pub fn check_access(&self) -> AccessState {
+        self.raw.check_access()
     }
+}
+
+impl Clipboard {
+    pub fn read_primary(&self) -> Option<Result<String, Error>> {
+        if let Err(error) = self.check_readable(Operation::ReadPrimary) {
+            return Some(Err(error));
+        }
+
+        let start = Recorder::start();
+        let result = self.raw.read_primary()?;
+        self.record(Operation::ReadPrimary, start, result.is_ok());
+
+        Some(
+            result
+                .map_err(|error| self.error(Operation::ReadPrimary, error))
+                .and_then(|text| {
+                    let text = self.admit(Operation::ReadPrimary, text)?;
 
-    pub fn write_primary(&mut self, contents: String) -> Option<Result<(), Box<dyn Error>>> {
-        self.raw.write_primary(contents)
+                    Ok(self.normalize(text))
+                }),
+        )
     }
+
+    pub fn write_primary<'a>(
+        &mut self,
+        contents: impl Into<Cow<'a, str>>,
+    ) -> Option<Result<(), Error>> {
+        if let Err(error) = self.check_writable(Operation::WritePrimary) {
+            return Some(Err(error));
+        }
+
+        let contents = self.normalize(contents.into().into_owned());
+
+        let start = Recorder::start();
+        let result = self.raw.write_primary(contents)?;
+        self.record(Operation::WritePrimary, start, result.is_ok());
+
+        Some(result.map_err(|error| self.error(Operation::WritePrimary, error)))
+    }
+
+    /// Reads the text of the given [`Selection`].
+    ///
+    /// Returns `None` if the backend has no such selection.
+    pub fn read_selection(
+        &self,
+        selection: Selection,
+    ) -> Option<Result<String, Error>> {
+        match selection {
+            Selection::Clipboard => Some(self.read()),
+            Selection::Primary => self.read_primary(),
+        }
+    }
+
+    /// Writes text to the given [`Selection`].
+    ///
+    /// Returns `None` if the backend has no such selection.
+    pub fn write_selection<'a>(
+        &mut self,
+        selection: Selection,
+        contents: impl Into<Cow<'a, str>>,
+    ) -> Option<Result<(), Error>> {
+        match selection {
+            Selection::Clipboard => Some(self.write(contents)),
+            Selection::Primary => self.write_primary(contents),
+        }
+    }
+}
+
+/// How written contents are marked for clipboard history tools.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WriteOptions {
+    /// Whether the contents are only meant for an imminent paste, and must
+    /// not be kept in clipboard histories.
+    pub transient: bool,
+    /// Whether the contents are a secret, like a password, that clipboard
+    /// monitors must neither store nor show.
+    pub concealed: bool,
+}
+
+/// A selection of the display server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selection {
+    /// The clipboard, filled by explicit copy commands.
+    Clipboard,
+    /// The primary selection, filled by selecting text and pasted with the
+    /// middle mouse button on X11 and Wayland.
+    Primary,
 }
 
 pub trait ClipboardProvider {
-    fn read(&self) -> Result<String, Box<dyn Error>>;
+    fn read(&self) -> Result<String, Box<dyn std::error::Error>>;
+
+    fn write(
+        &mut self,
+        contents: String,
+    ) -> Result<(), Box<dyn std::error::Error>>;
+
+    fn read_primary(
+        &self,
+    ) -> Option<Result<String, Box<dyn std::error::Error>>> {
+        None
+    }
+
+    fn read_with_formats(
+        &self,
+    ) -> Option<Result<TextWithFormats, Box<dyn std::error::Error>>> {
+        None
+    }
+
+    fn read_text_preview(
+        &self,
+        max_bytes: usize,
+    ) -> Result<String, Box<dyn std::error::Error>> {
+        let mut text = self.read()?;
+
+        if max_bytes < text.len() {
+            let end = (0..=max_bytes)
+                .rev()
+                .find(|&index| text.is_char_boundary(index))
+                .unwrap_or(0);
 
-    fn write(&mut self, contents: String) -> Result<(), Box<dyn Error>>;
+            text.truncate(end);
+        }
+
+        Ok(text)
+    }
+
+    fn write_primary(
+        &mut self,
+        _contents: String,
+    ) -> Option<Result<(), Box<dyn std::error::Error>>> {
+        None
+    }
+
+    fn read_format(
+        &self,
+        _format: &Format,
+    ) -> Option<Result<Vec<u8>, Box<dyn std::error::Error>>> {
+        None
+    }
+
+    fn available_formats(
+        &self,
+    ) -> Option<Result<Vec<Format>, Box<dyn std::error::Error>>> {
+        None
+    }
+
+    fn read_all(
+        &self,
+    ) -> Option<Result<Representations, Box<dyn std::error::Error>>> {
+        let formats = match self.available_formats()? {
+            Ok(formats) => formats,
+            Err(error) => return Some(Err(error)),
+        };
+
+        let mut representations = HashMap::new();
+
+        for format in formats {
+            if let Ok(data) = self.read_format(&format)? {
+                let _ = representations.insert(format, data);
+            }
+        }
+
+        Some(Ok(representations))
+    }
+
+    fn read_html(&self) -> Option<Result<String, Box<dyn std::error::Error>>> {
+        None
+    }
+
+    fn read_format_to(
+        &self,
+        format: &Format,
+        writer: &mut dyn Write,
+    ) -> Option<Result<u64, Box<dyn std::error::Error>>> {
+        Some(self.read_format(format)?.and_then(|data| {
+            writer.write_all(&data)?;
+
+            Ok(data.len() as u64)
+        }))
+    }
 
-    fn read_primary(&self) -> Option<Result<String, Box<dyn Error>>> {
+    fn available_of(
+        &self,
+        _formats: &[Format],
+    ) -> Option<Result<Vec<bool>, Box<dyn std::error::Error>>> {
         None
     }
 
-    fn write_primary(&mut self, _contents: String) -> Option<Result<(), Box<dyn Error>>> {
+    fn format_size(&self, _format: &Format) -> Option<u64> {
         None
     }
+
+    fn write_text_types(
+        &mut self,
+        _contents: String,
+        _formats: &[Format],
+    ) -> Option<Result<(), Box<dyn std::error::Error>>> {
+        None
+    }
+
+    fn write_data(
+        &mut self,
+        _items: &[(Format, Vec<u8>)],
+    ) -> Option<Result<(), Box<dyn std::error::Error>>> {
+        None
+    }
+
+    fn write_promised(
+        &mut self,
+        _items: &[(Format, Vec<u8>)],
+        _promises: &[(Format, Promise)],
+    ) -> Option<Result<(), Box<dyn std::error::Error>>> {
+        None
+    }
+
+    fn clear(&mut self) -> Option<Result<(), Box<dyn std::error::Error>>> {
+        None
+    }
+
+    fn read_items(
+        &self,
+    ) -> Option<Result<Vec<Item>, Box<dyn std::error::Error>>> {
+        None
+    }
+
+    fn write_items(
+        &mut self,
+        _items: &[Item],
+    ) -> Option<Result<(), Box<dyn std::error::Error>>> {
+        None
+    }
+
+    fn change_count(&self) -> Option<Result<u64, Box<dyn std::error::Error>>> {
+        None
+    }
+
+    fn is_owner(
+        &self,
+        _selection: Selection,
+    ) -> Option<Result<bool, Box<dyn std::error::Error>>> {
+        None
+    }
+
+    fn write_with_ttl(
+        &mut self,
+        _contents: String,
+        _ttl: Duration,
+    ) -> Option<Result<ClearTimer, Box<dyn std::error::Error>>> {
+        None
+    }
+
+    fn read_files(
+        &self,
+    ) -> Option<Result<Vec<PathBuf>, Box<dyn std::error::Error>>> {
+        None
+    }
+
+    fn write_files(
+        &mut self,
+        _paths: &[PathBuf],
+    ) -> Option<Result<(), Box<dyn std::error::Error>>> {
+        None
+    }
+
+    fn register_converter(
+        &mut self,
+        _from: &Format,
+        _to: &Format,
+        _converter: Converter,
+    ) -> bool {
+        false
+    }
+
+    fn set_read_timeout(
+        &mut self,
+        _format: Option<&Format>,
+        _timeout: Duration,
+    ) -> bool {
+        false
+    }
+
+    fn check_access(&self) -> AccessState {
+        AccessState::Unknown
+    }
+
+    /// The name of the backend, as reported in the [`Context`] of errors.
+    fn backend(&self) -> &'static str {
+        "custom"
+    }
+}
+
+/// Whether the application may read the clipboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessState {
+    /// Reads are allowed.
+    Granted,
+    /// Reads are blocked.
+    Denied,
+    /// The user will be asked when the clipboard is read.
+    Prompt,
+    /// The backend cannot tell.
+    Unknown,
 }
@@ -0,0 +1,148 @@
+//! Clipboard operation metrics.
+use crate::Operation;
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// The number of latency buckets. Bucket `i` holds the latencies below
+/// `2^i` microseconds, and the last one holds anything slower.
+const BUCKETS: usize = 32;
+
+/// A snapshot of the operations performed by a
+/// [`Clipboard`](crate::Clipboard).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Metrics {
+    /// The name of the backend that performed the operations.
+    pub backend: &'static str,
+    operations: HashMap<Operation, OperationMetrics>,
+}
+
+impl Metrics {
+    /// Returns the [`OperationMetrics`] of the given [`Operation`], if it was
+    /// performed at least once.
+    pub fn operation(&self, operation: Operation) -> Option<&OperationMetrics> {
+        self.operations.get(&operation)
+    }
+
+    /// Returns the [`OperationMetrics`] of every performed [`Operation`].
+    pub fn operations(
+        &self,
+    ) -> impl Iterator<Item = (Operation, &OperationMetrics)> {
+        self.operations
+            .iter()
+            .map(|(&operation, metrics)| (operation, metrics))
+    }
+}
+
+/// The count and latency of an [`Operation`].
+///
+/// Quantiles are approximated by the upper bound of a power-of-two bucket
+/// of microseconds, capped at the [`max`](Self::max) latency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OperationMetrics {
+    /// The number of times the operation was performed.
+    pub count: u64,
+    /// The number of times the operation failed.
+    pub errors: u64,
+    /// The median latency.
+    pub p50: Duration,
+    /// The 90th percentile latency.
+    pub p90: Duration,
+    /// The 99th percentile latency.
+    pub p99: Duration,
+    /// The slowest latency.
+    pub max: Duration,
+}
+
+/// Records the operations of a [`Clipboard`](crate::Clipboard).
+#[derive(Debug, Default)]
+pub(crate) struct Recorder {
+    histograms: HashMap<Operation, Histogram>,
+}
+
+impl Recorder {
+    /// Starts timing an operation.
+    ///
+    /// Time is unavailable on `wasm32`, where operations are recorded with
+    /// no latency.
+    pub fn start() -> Option<Instant> {
+        if cfg!(target_arch = "wasm32") {
+            None
+        } else {
+            Some(Instant::now())
+        }
+    }
+
+    pub fn record(
+        &mut self,
+        operation: Operation,
+        start: Option<Instant>,
+        success: bool,
+    ) {
+        let latency = start.map(|start| start.elapsed()).unwrap_or_default();
+
+        self.histograms
+            .entry(operation)
+            .or_default()
+            .record(latency, success);
+    }
+
+    pub fn snapshot(&self, backend: &'static str) -> Metrics {
+        Metrics {
+            backend,
+            operations: self
+                .histograms
+                .iter()
+                .map(|(&operation, histogram)| (operation, histogram.metrics()))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Histogram {
+    buckets: [u64; BUCKETS],
+    count: u64,
+    errors: u64,
+    max: Duration,
+}
+
+impl Histogram {
+    fn record(&mut self, latency: Duration, success: bool) {
+        let micros = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+        let bucket = (u64::BITS - micros.leading_zeros()) as usize;
+
+        self.buckets[bucket.min(BUCKETS - 1)] += 1;
+        self.count += 1;
+        self.errors += u64::from(!success);
+        self.max = self.max.max(latency);
+    }
+
+    fn metrics(&self) -> OperationMetrics {
+        OperationMetrics {
+            count: self.count,
+            errors: self.errors,
+            p50: self.quantile(50),
+            p90: self.quantile(90),
+            p99: self.quantile(99),
+            max: self.max,
+        }
+    }
+
+    fn quantile(&self, percent: u64) -> Duration {
+        let rank = (self.count * percent).div_ceil(100).max(1);
+        let mut seen = 0;
+
+        for (bucket, &count) in self.buckets.iter().enumerate() {
+            seen += count;
+
+            if seen >= rank {
+                let bound = Duration::from_micros(1 << bucket);
+
+                return bound.min(self.max);
+            }
+        }
+
+        self.max
+    }
+}
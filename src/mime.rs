@@ -0,0 +1,248 @@
+//! Mapping between MIME types and platform-native format names.
+//!
+//! MIME types are what X11, Wayland, iOS and Android use natively. macOS
+//! names formats with UTIs, and Windows with clipboard format names.
+//!
+//! The names are those of the table behind [`formats::normalize`], so both
+//! modules always agree.
+use crate::files;
+use crate::formats::{self, Format};
+use crate::platform;
+
+/// Returns the preferred UTI of a MIME type, if known (e.g.
+/// `public.utf8-plain-text` for `text/plain`).
+pub fn to_uti(mime: &str) -> Option<&'static str> {
+    native(formats::MACOS, mime)
+}
+
+/// Returns the MIME type of a UTI, if known.
+pub fn from_uti(uti: &str) -> Option<&'static str> {
+    mime(formats::MACOS, uti)
+}
+
+/// Returns the preferred Windows clipboard format name of a MIME type, if
+/// known (e.g. `CF_UNICODETEXT` for `text/plain`).
+pub fn to_windows(mime: &str) -> Option<&'static str> {
+    native(formats::WINDOWS, mime)
+}
+
+/// Returns the MIME type of a Windows clipboard format name, if known.
+pub fn from_windows(name: &str) -> Option<&'static str> {
+    mime(formats::WINDOWS, name)
+}
+
+/// Returns the [`Format`] of a MIME type on the current platform.
+///
+/// MIME types without a known native name are used as is, which is how
+/// custom formats are named on every platform. Beware that the data of a
+/// native format may be encoded differently than that of its MIME type,
+/// like UTF-16 for `CF_UNICODETEXT`. [`to_native_data`] converts the data
+/// as well.
+pub fn to_native(mime: &str) -> Format {
+    let essence = essence(mime);
+
+    let native = if cfg!(target_os = "macos") {
+        to_uti(essence)
+    } else if cfg!(target_os = "windows") {
+        to_windows(essence)
+    } else {
+        None
+    };
+
+    match native {
+        Some(native) => Format::new(native),
+        None => Format::new(mime.to_owned()),
+    }
+}
+
+/// Returns the [`Format`] of a MIME type on the current platform along with
+/// the data of that format.
+///
+/// The data is converted where the native format encodes it differently,
+/// like `text/uri-list` into the `DROPFILES` of `CF_HDROP`, or a BMP file
+/// into the `CF_DIB` without its file header. MIME types whose data cannot
+/// be converted, like `image/emf` whose native format holds a handle, keep
+/// their name and data as is.
+pub fn to_native_data(mime: &str, data: &[u8]) -> (Format, Vec<u8>) {
+    match essence(mime) {
+        "text/plain" => platform::text_data(&String::from_utf8_lossy(data)),
+        "text/html" => platform::html_data(&String::from_utf8_lossy(data)),
+        _ => convert(mime, data),
+    }
+}
+
+/// Returns the native [`Format`] of a MIME type other than text or HTML,
+/// along with its converted data.
+fn convert(mime: &str, data: &[u8]) -> (Format, Vec<u8>) {
+    let format = to_native(mime);
+
+    let converted = if cfg!(target_os = "macos") {
+        macos_data(format.name(), data)
+    } else if cfg!(target_os = "windows") {
+        windows_data(format.name(), data)
+    } else {
+        Some(data.to_vec())
+    };
+
+    match converted {
+        Some(data) => (format, data),
+        None => (Format::new(mime.to_owned()), data.to_vec()),
+    }
+}
+
+/// Returns the MIME type of a [`Format`] of any platform, without
+/// parameters, if known.
+pub fn from_native(format: &Format) -> Option<&'static str> {
+    formats::normalize(format.name())
+}
+
+/// Strips the parameters of a MIME type, like `;charset=utf-8`.
+fn essence(mime: &str) -> &str {
+    mime.split(';').next().unwrap_or_default().trim()
+}
+
+/// Converts the data of a MIME type to that of the given UTI, if possible.
+fn macos_data(uti: &str, data: &[u8]) -> Option<Vec<u8>> {
+    match uti {
+        // A single URL, rather than a list
+        "public.file-url" => {
+            let files = files::parse_uri_list(&String::from_utf8_lossy(data));
+
+            match (&files.paths[..], &files.rejected[..]) {
+                ([path], []) => Some(
+                    files::to_uri_list(std::slice::from_ref(path))
+                        .trim_end()
+                        .as_bytes()
+                        .to_vec(),
+                ),
+                _ => None,
+            }
+        }
+        _ => Some(data.to_vec()),
+    }
+}
+
+/// Converts the data of a MIME type to that of the given Windows clipboard
+/// format, if possible.
+fn windows_data(name: &str, data: &[u8]) -> Option<Vec<u8>> {
+    match name {
+        "CF_HDROP" => {
+            let files = files::parse_uri_list(&String::from_utf8_lossy(data));
+
+            (!files.paths.is_empty() && files.rejected.is_empty())
+                .then(|| files::to_drop_files(&files.paths))
+        }
+        "CF_DIB" if data.starts_with(b"BM") => {
+            data.get(14..).map(<[u8]>::to_vec)
+        }
+        // The URL alone, in UTF-16
+        "UniformResourceLocatorW" => {
+            let url = String::from_utf8_lossy(data);
+            let url = url.lines().next().unwrap_or_default();
+
+            Some(
+                url.encode_utf16()
+                    .chain([0])
+                    .flat_map(u16::to_le_bytes)
+                    .collect(),
+            )
+        }
+        // Anything but a BMP file for a DIB, and a metafile, which is a
+        // handle
+        "CF_DIB" | "CF_ENHMETAFILE" => None,
+        _ => Some(data.to_vec()),
+    }
+}
+
+fn native(table: &[(&'static str, &str)], mime: &str) -> Option<&'static str> {
+    let mime = essence(mime);
+
+    table
+        .iter()
+        .find(|&&(_, candidate)| candidate.eq_ignore_ascii_case(mime))
+        .map(|&(native, _)| native)
+}
+
+fn mime(table: &[(&str, &'static str)], native: &str) -> Option<&'static str> {
+    table
+        .iter()
+        .find(|&&(candidate, _)| candidate == native)
+        .map(|&(_, mime)| mime)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_every_native_name() {
+        for &(uti, mime) in formats::MACOS {
+            assert_eq!(from_uti(uti), Some(mime));
+            assert_eq!(to_uti(mime).and_then(from_uti), Some(mime));
+        }
+
+        for &(name, mime) in formats::WINDOWS {
+            assert_eq!(from_windows(name), Some(mime));
+            assert_eq!(to_windows(mime).and_then(from_windows), Some(mime));
+        }
+    }
+
+    #[test]
+    fn prefers_the_first_native_name() {
+        assert_eq!(to_uti("text/plain"), Some("public.utf8-plain-text"));
+        assert_eq!(
+            to_windows("text/plain;charset=utf-8"),
+            Some("CF_UNICODETEXT")
+        );
+        assert_eq!(to_windows("image/bmp"), Some("CF_DIB"));
+        assert_eq!(to_uti("application/x-custom"), None);
+    }
+
+    #[test]
+    fn round_trips_native_formats() {
+        for mime in ["text/plain", "text/html", "image/png", "text/csv"] {
+            assert_eq!(from_native(&to_native(mime)), Some(mime));
+        }
+
+        assert_eq!(
+            to_native("application/x-custom"),
+            Format::new("application/x-custom")
+        );
+    }
+
+    #[test]
+    fn strips_the_file_header_of_bitmaps() {
+        let mut bmp = b"BM".to_vec();
+        bmp.resize(14, 0);
+        bmp.extend([40, 0, 0, 0]);
+
+        assert_eq!(windows_data("CF_DIB", &bmp), Some(vec![40, 0, 0, 0]));
+        assert_eq!(windows_data("CF_DIB", &[40, 0, 0, 0]), None);
+        assert_eq!(windows_data("CF_ENHMETAFILE", b"EMF"), None);
+    }
+
+    #[test]
+    fn converts_file_lists_to_drop_files() {
+        let drop_files =
+            windows_data("CF_HDROP", b"file:///a\r\n").unwrap_or_default();
+        let path = files::parse_uri_list("file:///a").paths.remove(0);
+        let path = path.to_string_lossy().encode_utf16().count();
+
+        // The header, then the path and the list ending with nuls
+        assert_eq!(drop_files[..4], 20u32.to_le_bytes());
+        assert_eq!(drop_files[16..20], 1u32.to_le_bytes());
+        assert_eq!(drop_files.len(), 20 + (path + 2) * 2);
+        assert_eq!(windows_data("CF_HDROP", b"https://example.com"), None);
+    }
+
+    #[test]
+    fn keeps_mime_types_that_cannot_be_converted() {
+        let (format, data) = to_native_data("image/emf", b"EMF");
+
+        if cfg!(target_os = "windows") {
+            assert_eq!(format, Format::new("image/emf"));
+        }
+
+        assert_eq!(data, b"EMF");
+    }
+}
@@ -0,0 +1,87 @@
+//! Restrictions of what a clipboard handle may do.
+
+/// What a [`Clipboard`](crate::Clipboard) handle is allowed to do.
+///
+/// A handle can only be restricted further, with
+/// [`into_read_only`](crate::Clipboard::into_read_only) and
+/// [`into_write_only`](crate::Clipboard::into_write_only), so code given a
+/// restricted handle cannot lift the restriction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Mode {
+    /// Both reads and writes are allowed.
+    #[default]
+    ReadWrite,
+    /// Only reads are allowed, like for a preview pane or a plugin that may
+    /// paste but must not overwrite what the user copied.
+    ReadOnly,
+    /// Only writes are allowed, like for a crash reporter that may copy
+    /// diagnostics but must not inspect what the user copied.
+    ///
+    /// Neither the contents nor their formats can be read. Only
+    /// [`change_count`](crate::Clipboard::change_count) is still available.
+    WriteOnly,
+    /// Neither reads nor writes are allowed, once a handle has been made
+    /// both read-only and write-only.
+    NoAccess,
+}
+
+impl Mode {
+    /// Returns the [`Mode`] allowing only what both modes allow.
+    pub(crate) fn restrict(self, other: Mode) -> Mode {
+        match (self, other) {
+            (Mode::ReadWrite, mode) | (mode, Mode::ReadWrite) => mode,
+            (Mode::ReadOnly, Mode::ReadOnly) => Mode::ReadOnly,
+            (Mode::WriteOnly, Mode::WriteOnly) => Mode::WriteOnly,
+            _ => Mode::NoAccess,
+        }
+    }
+
+    /// Returns whether the [`Mode`] allows reads.
+    pub fn can_read(self) -> bool {
+        matches!(self, Mode::ReadWrite | Mode::ReadOnly)
+    }
+
+    /// Returns whether the [`Mode`] allows writes.
+    pub fn can_write(self) -> bool {
+        matches!(self, Mode::ReadWrite | Mode::WriteOnly)
+    }
+}
+
+/// The error of a write refused because the handle may not write, being
+/// read-only or without any access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("clipboard handle may not write")]
+pub struct ReadOnly;
+
+/// The error of a read refused because the handle may not read, being
+/// write-only or without any access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("clipboard handle may not read")]
+pub struct WriteOnly;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restrictions_only_narrow() {
+        for mode in [
+            Mode::ReadWrite,
+            Mode::ReadOnly,
+            Mode::WriteOnly,
+            Mode::NoAccess,
+        ] {
+            assert_eq!(mode.restrict(Mode::ReadWrite), mode);
+
+            let read_only = mode.restrict(Mode::ReadOnly);
+            let write_only = mode.restrict(Mode::WriteOnly);
+
+            assert!(!read_only.can_write());
+            assert_eq!(read_only.can_read(), mode.can_read());
+            assert!(!write_only.can_read());
+            assert_eq!(write_only.can_write(), mode.can_write());
+        }
+
+        assert_eq!(Mode::ReadOnly.restrict(Mode::WriteOnly), Mode::NoAccess);
+    }
+}
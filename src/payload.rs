@@ -0,0 +1,155 @@
+//! Where read data is kept.
+use std::collections::hash_map::RandomState;
+use std::fs::{self, File, OpenOptions};
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Where the data of a read is kept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PayloadLocation {
+    /// The data is in memory.
+    Memory(Vec<u8>),
+    /// The data exceeded the spill threshold and was written to a temporary
+    /// file, which the caller is responsible for removing.
+    File(PathBuf),
+}
+
+/// Writes a payload into a new temporary file, removing it on failure.
+pub(crate) fn spill(
+    write: impl FnOnce(
+        &mut dyn Write,
+    ) -> Option<Result<u64, Box<dyn std::error::Error>>>,
+) -> Option<Result<PayloadLocation, Box<dyn std::error::Error>>> {
+    let mut spill = match Spill::create() {
+        Ok(spill) => spill,
+        Err(error) => return Some(Err(Box::new(error))),
+    };
+
+    write(&mut spill.file).map(|result| {
+        let _ = result?;
+        spill.file.sync_all()?;
+
+        Ok(PayloadLocation::File(spill.keep()))
+    })
+}
+
+/// A temporary file that is removed when dropped, unless it is kept.
+///
+/// This covers failed writes and panics alike.
+struct Spill {
+    path: PathBuf,
+    file: File,
+    is_kept: bool,
+}
+
+impl Spill {
+    /// The number of names tried before giving up.
+    const ATTEMPTS: usize = 16;
+
+    /// Creates a new temporary file that only the current user can access.
+    ///
+    /// Its name is unpredictable, and it is never opened if it exists
+    /// already, so other users can neither read it nor plant it.
+    fn create() -> io::Result<Self> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        for _ in 0..Self::ATTEMPTS {
+            let path = std::env::temp_dir().join(format!(
+                "window_clipboard-{}-{}-{:016x}",
+                std::process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed),
+                RandomState::new().build_hasher().finish(),
+            ));
+
+            match options().open(&path) {
+                Ok(file) => {
+                    return Ok(Spill {
+                        path,
+                        file,
+                        is_kept: false,
+                    })
+                }
+                Err(error) if error.kind() == io::ErrorKind::AlreadyExists => {}
+                Err(error) => return Err(error),
+            }
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "no unused temporary file name found",
+        ))
+    }
+
+    /// Keeps the file past the drop, returning its path.
+    fn keep(&mut self) -> PathBuf {
+        self.is_kept = true;
+        self.path.clone()
+    }
+}
+
+impl Drop for Spill {
+    fn drop(&mut self) {
+        if !self.is_kept {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn options() -> OpenOptions {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let mut options = OpenOptions::new();
+    let _ = options.write(true).create_new(true).mode(0o600);
+
+    options
+}
+
+#[cfg(not(unix))]
+fn options() -> OpenOptions {
+    // The temporary directory of Windows is private to each user already.
+    let mut options = OpenOptions::new();
+    let _ = options.write(true).create_new(true);
+
+    options
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_written_payloads() {
+        let location = spill(|file| {
+            Some(file.write_all(b"data").map(|_| 4).map_err(Box::from))
+        });
+
+        let Some(Ok(PayloadLocation::File(path))) = location else {
+            panic!("payload not spilled");
+        };
+
+        assert_eq!(fs::read(&path).unwrap(), b"data");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn removes_files_that_are_not_kept() {
+        let spill = Spill::create().unwrap();
+        let path = spill.path.clone();
+
+        assert!(path.exists());
+        drop(spill);
+        assert!(!path.exists());
+    }
+}
@@ -3,6 +3,12 @@ use crate::ClipboardProvider;
 use raw_window_handle::HasDisplayHandle;
 use std::error::Error;
 
+pub const BACKEND: &str = "android";
+pub const RTF: &str = "text/rtf";
+pub const CSV: &str = "text/csv";
+pub const MATHML: &str = "application/mathml+xml";
+pub const PNG: &str = "image/png";
+
 pub fn connect<W: HasDisplayHandle>(
     _window: &W,
 ) -> Result<Box<dyn ClipboardProvider>, Box<dyn Error>> {
@@ -36,7 +42,41 @@ impl ClipboardProvider for Clipboard {
         Err(Box::new(AndroidClipboardError::Unimplemented))
     }
 
-    fn write(&mut self, contents: String) -> Result<(), Box<dyn Error>> {
+    fn write(&mut self, _contents: String) -> Result<(), Box<dyn Error>> {
         Err(Box::new(AndroidClipboardError::Unimplemented))
     }
+
+    fn backend(&self) -> &'static str {
+        BACKEND
+    }
+}
+
+pub fn error_code(
+    _error: &(dyn Error + 'static),
+) -> Option<crate::PlatformCode> {
+    None
+}
+
+pub fn error_kind(error: &(dyn Error + 'static)) -> Option<crate::ErrorKind> {
+    error
+        .is::<AndroidClipboardError>()
+        .then_some(crate::ErrorKind::BackendUnavailable)
+}
+
+/// Returns `text` as a `text/plain;charset=utf-8` representation.
+pub fn text_data(text: &str) -> (crate::Format, Vec<u8>) {
+    (crate::Format::new(crate::TEXT), text.as_bytes().to_vec())
+}
+
+/// Returns `html` as a `text/html` representation.
+pub fn html_data(html: &str) -> (crate::Format, Vec<u8>) {
+    (crate::Format::new("text/html"), html.as_bytes().to_vec())
+}
+
+/// Returns the representations that mark written contents as transient or
+/// concealed, of which there are none on this platform.
+pub fn write_markers(
+    _options: &crate::WriteOptions,
+) -> Vec<(crate::Format, Vec<u8>)> {
+    Vec::new()
 }
@@ -4,6 +4,12 @@ use raw_window_handle::HasDisplayHandle;
 
 struct Dummy;
 
+pub const BACKEND: &str = "dummy";
+pub const RTF: &str = "text/rtf";
+pub const CSV: &str = "text/csv";
+pub const MATHML: &str = "application/mathml+xml";
+pub const PNG: &str = "image/png";
+
 pub fn connect<W: HasDisplayHandle>(
     _window: &W,
 ) -> Result<Box<dyn ClipboardProvider>, Box<dyn std::error::Error>> {
@@ -21,6 +27,42 @@ impl ClipboardProvider for Dummy {
     ) -> Result<(), Box<dyn std::error::Error>> {
         Err(Box::new(Error::Unimplemented))
     }
+
+    fn backend(&self) -> &'static str {
+        BACKEND
+    }
+}
+
+pub fn error_code(
+    _error: &(dyn std::error::Error + 'static),
+) -> Option<crate::PlatformCode> {
+    None
+}
+
+pub fn error_kind(
+    error: &(dyn std::error::Error + 'static),
+) -> Option<crate::ErrorKind> {
+    error
+        .is::<Error>()
+        .then_some(crate::ErrorKind::BackendUnavailable)
+}
+
+/// Returns `text` as a `text/plain;charset=utf-8` representation.
+pub fn text_data(text: &str) -> (crate::Format, Vec<u8>) {
+    (crate::Format::new(crate::TEXT), text.as_bytes().to_vec())
+}
+
+/// Returns `html` as a `text/html` representation.
+pub fn html_data(html: &str) -> (crate::Format, Vec<u8>) {
+    (crate::Format::new("text/html"), html.as_bytes().to_vec())
+}
+
+/// Returns the representations that mark written contents as transient or
+/// concealed, of which there are none on this platform.
+pub fn write_markers(
+    _options: &crate::WriteOptions,
+) -> Vec<(crate::Format, Vec<u8>)> {
+    Vec::new()
 }
 
 #[derive(Debug, Clone, Copy, thiserror::Error)]
@@ -1,11 +1,22 @@
-use crate::ClipboardProvider;
+use crate::expiry::ClearTimer;
+use crate::{
+    AccessState, ClipboardProvider, Converter, ErrorKind, Format, PlatformCode,
+    Selection, WriteOptions,
+};
 
 use raw_window_handle::{HasDisplayHandle, RawDisplayHandle};
 use std::error::Error;
+use std::time::Duration;
 
 pub use clipboard_wayland as wayland;
 pub use clipboard_x11 as x11;
 
+pub const BACKEND: &str = "linux";
+pub const RTF: &str = "text/rtf";
+pub const CSV: &str = "text/csv";
+pub const MATHML: &str = "application/mathml+xml";
+pub const PNG: &str = "image/png";
+
 pub unsafe fn connect<W: HasDisplayHandle>(
     window: &W,
 ) -> Result<Box<dyn ClipboardProvider>, Box<dyn Error>> {
@@ -19,6 +30,50 @@ pub unsafe fn connect<W: HasDisplayHandle>(
     Ok(clipboard)
 }
 
+pub fn error_code(error: &(dyn Error + 'static)) -> Option<PlatformCode> {
+    let error = error.downcast_ref::<x11::Error>()?.server_error()?;
+
+    Some(PlatformCode::X11 {
+        error_code: error.error_code,
+        major_opcode: error.major_opcode,
+        minor_opcode: error.minor_opcode,
+    })
+}
+
+pub fn error_kind(error: &(dyn Error + 'static)) -> Option<ErrorKind> {
+    match error.downcast_ref::<x11::Error>()? {
+        x11::Error::Unavailable(_) => Some(ErrorKind::Empty),
+        x11::Error::UnexpectedType(_) | x11::Error::InvalidUtf8(_) => {
+            Some(ErrorKind::ConversionFailed)
+        }
+        x11::Error::ConnectionFailed(_) => Some(ErrorKind::BackendUnavailable),
+        _ => None,
+    }
+}
+
+/// Returns `text` as an `UTF8_STRING` representation.
+pub fn text_data(text: &str) -> (Format, Vec<u8>) {
+    (Format::new("UTF8_STRING"), text.as_bytes().to_vec())
+}
+
+/// Returns `html` as a `text/html` representation.
+pub fn html_data(html: &str) -> (Format, Vec<u8>) {
+    (Format::new("text/html"), html.as_bytes().to_vec())
+}
+
+/// Returns the representations that mark written contents as transient or
+/// concealed.
+///
+/// Concealed contents carry the `x-kde-passwordManagerHint` that Klipper
+/// and other history tools skip. Transient contents have no marker.
+pub fn write_markers(options: &WriteOptions) -> Vec<(Format, Vec<u8>)> {
+    if options.concealed {
+        vec![(Format::new("x-kde-passwordManagerHint"), b"secret".to_vec())]
+    } else {
+        Vec::new()
+    }
+}
+
 impl ClipboardProvider for wayland::Clipboard {
     fn read(&self) -> Result<String, Box<dyn Error>> {
         self.read()
@@ -32,9 +87,20 @@ impl ClipboardProvider for wayland::Clipboard {
         self.write(contents)
     }
 
-    fn write_primary(&mut self, contents: String) -> Option<Result<(), Box<dyn Error>>> {
+    fn write_primary(
+        &mut self,
+        contents: String,
+    ) -> Option<Result<(), Box<dyn Error>>> {
         Some(self.write_primary(contents))
     }
+
+    fn check_access(&self) -> AccessState {
+        AccessState::Granted
+    }
+
+    fn backend(&self) -> &'static str {
+        "wayland"
+    }
 }
 
 impl ClipboardProvider for x11::Clipboard {
@@ -46,11 +112,143 @@ impl ClipboardProvider for x11::Clipboard {
         Some(self.read_primary().map_err(Box::from))
     }
 
+    fn read_text_preview(
+        &self,
+        max_bytes: usize,
+    ) -> Result<String, Box<dyn Error>> {
+        self.read_preview(max_bytes).map_err(Box::from)
+    }
+
     fn write(&mut self, contents: String) -> Result<(), Box<dyn Error>> {
         self.write(contents).map_err(Box::from)
     }
 
-    fn write_primary(&mut self, contents: String) -> Option<Result<(), Box<dyn Error>>> {
+    fn write_primary(
+        &mut self,
+        contents: String,
+    ) -> Option<Result<(), Box<dyn Error>>> {
         Some(self.write_primary(contents).map_err(Box::from))
     }
+
+    fn read_format(
+        &self,
+        format: &Format,
+    ) -> Option<Result<Vec<u8>, Box<dyn Error>>> {
+        Some(self.read_target(format.name()).map_err(Box::from))
+    }
+
+    fn read_html(&self) -> Option<Result<String, Box<dyn Error>>> {
+        Some(
+            self.read_target("text/html")
+                .map_err(Box::from)
+                .and_then(|html| Ok(String::from_utf8(html)?)),
+        )
+    }
+
+    fn available_formats(&self) -> Option<Result<Vec<Format>, Box<dyn Error>>> {
+        Some(
+            self.targets()
+                .map(|targets| targets.into_iter().map(Format::new).collect())
+                .map_err(Box::from),
+        )
+    }
+
+    fn available_of(
+        &self,
+        formats: &[Format],
+    ) -> Option<Result<Vec<bool>, Box<dyn Error>>> {
+        let targets: Vec<_> = formats.iter().map(Format::name).collect();
+
+        Some(self.available_targets(&targets).map_err(Box::from))
+    }
+
+    fn write_text_types(
+        &mut self,
+        contents: String,
+        formats: &[Format],
+    ) -> Option<Result<(), Box<dyn Error>>> {
+        let targets: Vec<_> = formats.iter().map(Format::name).collect();
+
+        Some(self.write_text_types(contents, &targets).map_err(Box::from))
+    }
+
+    fn write_data(
+        &mut self,
+        items: &[(Format, Vec<u8>)],
+    ) -> Option<Result<(), Box<dyn Error>>> {
+        let items: Vec<_> = items
+            .iter()
+            .map(|(format, data)| (format.name(), data.as_slice()))
+            .collect();
+
+        Some(self.write_data(&items).map_err(Box::from))
+    }
+
+    fn clear(&mut self) -> Option<Result<(), Box<dyn Error>>> {
+        Some(self.clear().map_err(Box::from))
+    }
+
+    fn change_count(&self) -> Option<Result<u64, Box<dyn Error>>> {
+        Some(self.change_count().map_err(Box::from))
+    }
+
+    fn is_owner(
+        &self,
+        selection: Selection,
+    ) -> Option<Result<bool, Box<dyn Error>>> {
+        let owns = match selection {
+            Selection::Clipboard => self.owns_clipboard(),
+            Selection::Primary => self.owns_primary(),
+        };
+
+        Some(owns.map_err(Box::from))
+    }
+
+    fn write_with_ttl(
+        &mut self,
+        contents: String,
+        ttl: Duration,
+    ) -> Option<Result<ClearTimer, Box<dyn Error>>> {
+        Some(
+            self.write_revocable(contents)
+                .map(|revocable| {
+                    ClearTimer::spawn(ttl, move || {
+                        let _ = revocable.revoke();
+                    })
+                })
+                .map_err(Box::from),
+        )
+    }
+
+    fn register_converter(
+        &mut self,
+        from: &Format,
+        to: &Format,
+        converter: Converter,
+    ) -> bool {
+        self.register_converter(from.name(), to.name(), converter);
+
+        true
+    }
+
+    fn set_read_timeout(
+        &mut self,
+        format: Option<&Format>,
+        timeout: Duration,
+    ) -> bool {
+        match format {
+            Some(format) => self.set_target_timeout(format.name(), timeout),
+            None => self.set_timeout(timeout),
+        }
+
+        true
+    }
+
+    fn check_access(&self) -> AccessState {
+        AccessState::Granted
+    }
+
+    fn backend(&self) -> &'static str {
+        "x11"
+    }
 }
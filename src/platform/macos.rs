@@ -1,7 +1,22 @@
-use crate::ClipboardProvider;
+use crate::expiry::ClearTimer;
+use crate::{
+    AccessState, ClipboardProvider, ErrorKind, Format, Item, Promise,
+    Representations, TextWithFormats, WriteOptions,
+};
 
+use clipboard_macos::AccessBehavior;
 use raw_window_handle::HasDisplayHandle;
 use std::error::Error;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub const BACKEND: &str = "macos";
+pub const RTF: &str = "public.rtf";
+pub const CSV: &str = "public.comma-separated-values-text";
+pub const MATHML: &str = "application/mathml+xml";
+pub const PNG: &str = "public.png";
 
 pub fn connect<W: HasDisplayHandle>(
     _window: &W,
@@ -11,10 +26,248 @@ pub fn connect<W: HasDisplayHandle>(
 
 impl ClipboardProvider for clipboard_macos::Clipboard {
     fn read(&self) -> Result<String, Box<dyn Error>> {
-        self.read()
+        self.read().map_err(Box::from)
     }
 
     fn write(&mut self, contents: String) -> Result<(), Box<dyn Error>> {
-        self.write(contents)
+        self.write(contents).map_err(Box::from)
+    }
+
+    fn read_format(
+        &self,
+        format: &Format,
+    ) -> Option<Result<Vec<u8>, Box<dyn Error>>> {
+        Some(self.read_data(format.name()).map_err(Box::from))
+    }
+
+    fn read_html(&self) -> Option<Result<String, Box<dyn Error>>> {
+        Some(
+            self.read_data("public.html")
+                .map_err(Box::from)
+                .and_then(|html| Ok(String::from_utf8(html)?)),
+        )
+    }
+
+    fn available_formats(&self) -> Option<Result<Vec<Format>, Box<dyn Error>>> {
+        Some(
+            self.declared_types()
+                .map(|types| types.into_iter().map(Format::new).collect())
+                .map_err(Box::from),
+        )
+    }
+
+    fn read_with_formats(
+        &self,
+    ) -> Option<Result<TextWithFormats, Box<dyn Error>>> {
+        Some(
+            self.read_with_formats()
+                .map(|(text, types)| {
+                    (text, types.into_iter().map(Format::new).collect())
+                })
+                .map_err(Box::from),
+        )
+    }
+
+    fn read_all(&self) -> Option<Result<Representations, Box<dyn Error>>> {
+        Some(
+            self.read_all()
+                .map(|representations| {
+                    representations
+                        .into_iter()
+                        .map(|(type_, data)| (Format::new(type_), data))
+                        .collect()
+                })
+                .map_err(Box::from),
+        )
+    }
+
+    fn available_of(
+        &self,
+        formats: &[Format],
+    ) -> Option<Result<Vec<bool>, Box<dyn Error>>> {
+        let types: Vec<_> = formats.iter().map(Format::name).collect();
+
+        Some(self.available_types(&types).map_err(Box::from))
+    }
+
+    fn read_format_to(
+        &self,
+        format: &Format,
+        writer: &mut dyn Write,
+    ) -> Option<Result<u64, Box<dyn Error>>> {
+        Some(self.write_data_to(format.name(), writer).map_err(Box::from))
+    }
+
+    fn format_size(&self, format: &Format) -> Option<u64> {
+        self.data_size(format.name()).ok().map(|size| size as u64)
+    }
+
+    fn write_text_types(
+        &mut self,
+        contents: String,
+        formats: &[Format],
+    ) -> Option<Result<(), Box<dyn Error>>> {
+        let types: Vec<_> = formats.iter().map(Format::name).collect();
+
+        Some(self.write_text_types(contents, &types).map_err(Box::from))
+    }
+
+    fn write_data(
+        &mut self,
+        items: &[(Format, Vec<u8>)],
+    ) -> Option<Result<(), Box<dyn Error>>> {
+        let items: Vec<_> = items
+            .iter()
+            .map(|(format, data)| (format.name(), data.as_slice()))
+            .collect();
+
+        Some(self.write_data(&items).map_err(Box::from))
+    }
+
+    fn write_promised(
+        &mut self,
+        items: &[(Format, Vec<u8>)],
+        promises: &[(Format, Promise)],
+    ) -> Option<Result<(), Box<dyn Error>>> {
+        let items: Vec<_> = items
+            .iter()
+            .map(|(format, data)| (format.name(), data.as_slice()))
+            .collect();
+        let promises = promises
+            .iter()
+            .map(|(format, promise)| {
+                (format.name().to_owned(), Arc::clone(promise))
+            })
+            .collect();
+
+        Some(self.write_promised(&items, promises).map_err(Box::from))
+    }
+
+    fn clear(&mut self) -> Option<Result<(), Box<dyn Error>>> {
+        Some(self.clear().map_err(Box::from))
+    }
+
+    fn read_items(&self) -> Option<Result<Vec<Item>, Box<dyn Error>>> {
+        Some(
+            self.read_items()
+                .map(|items| {
+                    items
+                        .into_iter()
+                        .map(|item| {
+                            item.into_iter()
+                                .map(|(type_, data)| (Format::new(type_), data))
+                                .collect()
+                        })
+                        .collect()
+                })
+                .map_err(Box::from),
+        )
     }
+
+    fn write_items(
+        &mut self,
+        items: &[Item],
+    ) -> Option<Result<(), Box<dyn Error>>> {
+        let items: Vec<Vec<_>> = items
+            .iter()
+            .map(|item| {
+                item.iter()
+                    .map(|(format, data)| (format.name(), data.as_slice()))
+                    .collect()
+            })
+            .collect();
+
+        Some(self.write_items(&items).map_err(Box::from))
+    }
+
+    fn change_count(&self) -> Option<Result<u64, Box<dyn Error>>> {
+        Some(Ok(self.change_count() as u64))
+    }
+
+    fn write_with_ttl(
+        &mut self,
+        contents: String,
+        ttl: Duration,
+    ) -> Option<Result<ClearTimer, Box<dyn Error>>> {
+        Some(
+            self.write_revocable(contents)
+                .map(|revocable| {
+                    ClearTimer::spawn(ttl, move || {
+                        let _ = revocable.revoke();
+                    })
+                })
+                .map_err(Box::from),
+        )
+    }
+
+    fn read_files(&self) -> Option<Result<Vec<PathBuf>, Box<dyn Error>>> {
+        Some(self.read_files().map_err(Box::from))
+    }
+
+    fn write_files(
+        &mut self,
+        paths: &[PathBuf],
+    ) -> Option<Result<(), Box<dyn Error>>> {
+        Some(self.write_files(paths).map_err(Box::from))
+    }
+
+    fn check_access(&self) -> AccessState {
+        match self.access_behavior() {
+            Ok(None) | Ok(Some(AccessBehavior::AlwaysAllow)) => {
+                AccessState::Granted
+            }
+            Ok(Some(AccessBehavior::Ask)) => AccessState::Prompt,
+            Ok(Some(AccessBehavior::AlwaysDeny)) => AccessState::Denied,
+            Ok(Some(AccessBehavior::Default)) | Err(_) => AccessState::Unknown,
+        }
+    }
+
+    fn backend(&self) -> &'static str {
+        BACKEND
+    }
+}
+
+pub fn error_code(
+    _error: &(dyn Error + 'static),
+) -> Option<crate::PlatformCode> {
+    None
+}
+
+pub fn error_kind(error: &(dyn Error + 'static)) -> Option<ErrorKind> {
+    match error.downcast_ref::<clipboard_macos::Error>()? {
+        clipboard_macos::Error::Unavailable(_) => Some(ErrorKind::Empty),
+        clipboard_macos::Error::InvalidUtf16(_) => {
+            Some(ErrorKind::ConversionFailed)
+        }
+        _ => None,
+    }
+}
+
+/// Returns `text` as a `public.utf8-plain-text` representation.
+pub fn text_data(text: &str) -> (Format, Vec<u8>) {
+    (
+        Format::new("public.utf8-plain-text"),
+        text.as_bytes().to_vec(),
+    )
+}
+
+/// Returns `html` as a `public.html` representation.
+pub fn html_data(html: &str) -> (Format, Vec<u8>) {
+    (Format::new("public.html"), html.as_bytes().to_vec())
+}
+
+/// Returns the representations that mark written contents as transient or
+/// concealed, following the conventions of nspasteboard.org.
+pub fn write_markers(options: &WriteOptions) -> Vec<(Format, Vec<u8>)> {
+    let mut markers = Vec::new();
+
+    if options.transient {
+        markers.push((Format::new("org.nspasteboard.TransientType"), vec![]));
+    }
+
+    if options.concealed {
+        markers.push((Format::new("org.nspasteboard.ConcealedType"), vec![]));
+    }
+
+    markers
 }
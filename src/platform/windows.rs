@@ -1,9 +1,26 @@
-use crate::ClipboardProvider;
+use crate::expiry::ClearTimer;
+use crate::{
+    AccessState, ClipboardProvider, ErrorKind, Format, PlatformCode, Promise,
+    WriteOptions,
+};
 
-use clipboard_win::{get_clipboard_string, set_clipboard_string};
+use clipboard_win::{
+    formats, get_clipboard_string, options, raw, set_clipboard_string,
+    ErrorCode,
+};
 use raw_window_handle::HasDisplayHandle;
 
+use std::cell::Cell;
 use std::error::Error;
+use std::ffi::c_void;
+use std::ptr;
+use std::time::Duration;
+
+pub const BACKEND: &str = "windows";
+pub const RTF: &str = "Rich Text Format";
+pub const CSV: &str = "Csv";
+pub const MATHML: &str = "MathML";
+pub const PNG: &str = "PNG";
 
 pub fn connect<W: HasDisplayHandle>(
     _window: &W,
@@ -13,12 +30,838 @@ pub fn connect<W: HasDisplayHandle>(
 
 pub struct Clipboard;
 
+/// The error of a clipboard operation performed by a [`Promise`].
+#[derive(Debug, thiserror::Error)]
+#[error("clipboard accessed from a promise")]
+struct Reentrancy;
+
+thread_local! {
+    /// Whether the current thread is rendering a [`Promise`].
+    static RENDERING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Fails if called from a [`Promise`].
+///
+/// Promises render while the pasting application holds the clipboard open,
+/// so opening it again would only time out.
+fn check_reentrancy() -> Result<(), Reentrancy> {
+    if RENDERING.with(Cell::get) {
+        Err(Reentrancy)
+    } else {
+        Ok(())
+    }
+}
+
+/// Opens the clipboard, unless called from a [`Promise`].
+fn open() -> Result<clipboard_win::Clipboard, Box<dyn Error>> {
+    check_reentrancy()?;
+
+    Ok(clipboard_win::Clipboard::new_attempts(10)?)
+}
+
+/// The error of a format name that could not be registered.
+#[derive(Debug, thiserror::Error)]
+#[error("could not register clipboard format {0}")]
+struct Unregistered(String);
+
 impl ClipboardProvider for Clipboard {
     fn read(&self) -> Result<String, Box<dyn Error>> {
+        check_reentrancy()?;
+
         Ok(get_clipboard_string()?)
     }
 
     fn write(&mut self, contents: String) -> Result<(), Box<dyn Error>> {
+        check_reentrancy()?;
+
         Ok(set_clipboard_string(&contents)?)
     }
+
+    fn read_format(
+        &self,
+        format: &Format,
+    ) -> Option<Result<Vec<u8>, Box<dyn Error>>> {
+        Some(read_format(format))
+    }
+
+    fn read_html(&self) -> Option<Result<String, Box<dyn Error>>> {
+        Some(read_html())
+    }
+
+    fn available_formats(&self) -> Option<Result<Vec<Format>, Box<dyn Error>>> {
+        Some(available_formats())
+    }
+
+    fn available_of(
+        &self,
+        formats: &[Format],
+    ) -> Option<Result<Vec<bool>, Box<dyn Error>>> {
+        Some(available_of(formats))
+    }
+
+    fn format_size(&self, format: &Format) -> Option<u64> {
+        let _clipboard = open().ok()?;
+
+        raw::size(format_id(format.name())?).map(|size| size.get() as u64)
+    }
+
+    fn write_text_types(
+        &mut self,
+        contents: String,
+        formats: &[Format],
+    ) -> Option<Result<(), Box<dyn Error>>> {
+        Some(write_text_types(&contents, formats))
+    }
+
+    fn write_data(
+        &mut self,
+        items: &[(Format, Vec<u8>)],
+    ) -> Option<Result<(), Box<dyn Error>>> {
+        Some(write_data(items))
+    }
+
+    fn write_promised(
+        &mut self,
+        items: &[(Format, Vec<u8>)],
+        promises: &[(Format, Promise)],
+    ) -> Option<Result<(), Box<dyn Error>>> {
+        Some(pump::write_promised(items, promises))
+    }
+
+    fn clear(&mut self) -> Option<Result<(), Box<dyn Error>>> {
+        Some(clear())
+    }
+
+    fn change_count(&self) -> Option<Result<u64, Box<dyn Error>>> {
+        // Without access to the clipboard, the sequence number is 0.
+        Some(Ok(raw::seq_num().map_or(0, |number| number.get().into())))
+    }
+
+    fn write_with_ttl(
+        &mut self,
+        contents: String,
+        ttl: Duration,
+    ) -> Option<Result<ClearTimer, Box<dyn Error>>> {
+        Some(write_with_ttl(&contents, ttl))
+    }
+
+    fn check_access(&self) -> AccessState {
+        AccessState::Granted
+    }
+
+    fn backend(&self) -> &'static str {
+        BACKEND
+    }
+}
+
+/// Reads the HTML fragment of the `HTML Format`.
+fn read_html() -> Result<String, Box<dyn Error>> {
+    let _clipboard = open()?;
+
+    let id = format_id("HTML Format")
+        .ok_or_else(|| Unregistered("HTML Format".to_owned()))?;
+
+    let mut html = Vec::new();
+    let _ = raw::get_html(id, &mut html)?;
+
+    Ok(String::from_utf8(html)?)
+}
+
+/// Returns the available formats, in the order they were written.
+fn available_formats() -> Result<Vec<Format>, Box<dyn Error>> {
+    let _clipboard = open()?;
+
+    Ok(raw::EnumFormats::new()
+        .filter_map(raw::format_name_big)
+        .map(Format::new)
+        .collect())
+}
+
+/// Returns whether each of the given formats is available, opening the
+/// clipboard once.
+fn available_of(formats: &[Format]) -> Result<Vec<bool>, Box<dyn Error>> {
+    let _clipboard = open()?;
+
+    Ok(formats
+        .iter()
+        .map(|format| {
+            format_id(format.name()).is_some_and(raw::is_format_avail)
+        })
+        .collect())
+}
+
+/// Reads the data of the given format.
+///
+/// `CF_METAFILEPICT` is read as EMF bytes, like `CF_ENHMETAFILE`, since its
+/// raw data is a `METAFILEPICT` struct holding a handle that is meaningless
+/// outside of the clipboard.
+fn read_format(format: &Format) -> Result<Vec<u8>, Box<dyn Error>> {
+    let _clipboard = open()?;
+
+    let id = format_id(format.name())
+        .ok_or_else(|| Unregistered(format.name().to_owned()))?;
+
+    if id == formats::CF_ENHMETAFILE || id == formats::CF_METAFILEPICT {
+        return read_enhanced_metafile();
+    }
+
+    let mut data = Vec::new();
+    let _ = raw::get_vec(id, &mut data)?;
+
+    Ok(data)
+}
+
+/// Reads the `CF_ENHMETAFILE` handle as raw EMF bytes.
+///
+/// The system synthesizes it from `CF_METAFILEPICT`, which converts pictures
+/// placed only in the older format to EMF. The clipboard must be open.
+fn read_enhanced_metafile() -> Result<Vec<u8>, Box<dyn Error>> {
+    #[link(name = "gdi32")]
+    extern "system" {
+        fn GetEnhMetaFileBits(
+            metafile: *mut c_void,
+            size: u32,
+            data: *mut u8,
+        ) -> u32;
+    }
+
+    let metafile = raw::get_clipboard_data(formats::CF_ENHMETAFILE)?.as_ptr();
+
+    let size = unsafe { GetEnhMetaFileBits(metafile, 0, ptr::null_mut()) };
+
+    if size == 0 {
+        return Err(Box::new(ErrorCode::last_system()));
+    }
+
+    let mut data = vec![0; size as usize];
+    let written =
+        unsafe { GetEnhMetaFileBits(metafile, size, data.as_mut_ptr()) };
+
+    if written == 0 {
+        return Err(Box::new(ErrorCode::last_system()));
+    }
+
+    data.truncate(written as usize);
+
+    Ok(data)
+}
+
+/// Returns the identifier of a clipboard format name, registering it if it
+/// is not a standard one.
+/// The predefined formats, by the name `raw::format_name_big` gives them.
+const PREDEFINED: &[(&str, u32)] = &[
+    ("CF_TEXT", formats::CF_TEXT),
+    ("CF_BITMAP", formats::CF_BITMAP),
+    ("CF_METAFILEPICT", formats::CF_METAFILEPICT),
+    ("CF_SYLK", formats::CF_SYLK),
+    ("CF_DIF", formats::CF_DIF),
+    ("CF_TIFF", formats::CF_TIFF),
+    ("CF_OEMTEXT", formats::CF_OEMTEXT),
+    ("CF_DIB", formats::CF_DIB),
+    ("CF_PALETTE", formats::CF_PALETTE),
+    ("CF_PENDATA", formats::CF_PENDATA),
+    ("CF_RIFF", formats::CF_RIFF),
+    ("CF_WAVE", formats::CF_WAVE),
+    ("CF_UNICODETEXT", formats::CF_UNICODETEXT),
+    ("CF_ENHMETAFILE", formats::CF_ENHMETAFILE),
+    ("CF_HDROP", formats::CF_HDROP),
+    ("CF_LOCALE", formats::CF_LOCALE),
+    ("CF_DIBV5", formats::CF_DIBV5),
+    ("CF_OWNERDISPLAY", formats::CF_OWNERDISPLAY),
+    ("CF_DSPTEXT", formats::CF_DSPTEXT),
+    ("CF_DSPBITMAP", formats::CF_DSPBITMAP),
+    ("CF_DSPMETAFILEPICT", formats::CF_DSPMETAFILEPICT),
+    ("CF_DSPENHMETAFILE", formats::CF_DSPENHMETAFILE),
+];
+
+/// Returns the id of the format `name`.
+///
+/// Predefined formats, including the `CF_PRIVATE` and `CF_GDIOBJ`
+/// ranges, map to their fixed ids. Any other name is registered.
+fn format_id(name: &str) -> Option<u32> {
+    if let Some(&(_, id)) = PREDEFINED.iter().find(|(n, _)| *n == name) {
+        return Some(id);
+    }
+
+    let ranges = [
+        (
+            "CF_PRIVATE",
+            formats::CF_PRIVATEFIRST,
+            formats::CF_PRIVATELAST,
+        ),
+        ("CF_GDIOBJ", formats::CF_GDIOBJFIRST, formats::CF_GDIOBJLAST),
+    ];
+
+    for (prefix, first, last) in ranges {
+        let offset = name
+            .strip_prefix(prefix)
+            .and_then(|offset| offset.parse::<u32>().ok());
+
+        if let Some(offset) = offset {
+            return (offset <= last - first).then(|| first + offset);
+        }
+    }
+
+    raw::register_format(name).map(|id| id.get())
+}
+
+/// Writes `contents` as the given formats.
+///
+/// `CF_TEXT` and `CF_OEMTEXT` are written as ASCII, replacing any other
+/// character with `?`. Formats other than the standard text ones are
+/// registered by name and written as UTF-8.
+fn write_text_types(
+    contents: &str,
+    formats: &[Format],
+) -> Result<(), Box<dyn Error>> {
+    let _clipboard = open()?;
+
+    raw::empty()?;
+
+    for format in formats {
+        match format.name() {
+            "CF_UNICODETEXT" => {
+                raw::set_string_with(contents, options::NoClear)?;
+            }
+            "CF_TEXT" => {
+                raw::set_without_clear(formats::CF_TEXT, &ascii(contents))?;
+            }
+            "CF_OEMTEXT" => {
+                raw::set_without_clear(formats::CF_OEMTEXT, &ascii(contents))?;
+            }
+            name => {
+                let id = raw::register_format(name)
+                    .ok_or_else(|| Unregistered(name.to_owned()))?;
+
+                raw::set_without_clear(id.get(), contents.as_bytes())?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Encodes `contents` as a nul-terminated ASCII string.
+fn ascii(contents: &str) -> Vec<u8> {
+    contents
+        .chars()
+        .map(|c| if c.is_ascii() { c as u8 } else { b'?' })
+        .chain(std::iter::once(0))
+        .collect()
+}
+
+pub fn error_code(error: &(dyn Error + 'static)) -> Option<PlatformCode> {
+    error
+        .downcast_ref::<ErrorCode>()
+        .map(|code| PlatformCode::Win32(code.raw_code() as u32))
+}
+
+pub fn error_kind(error: &(dyn Error + 'static)) -> Option<ErrorKind> {
+    error
+        .is::<Unregistered>()
+        .then_some(ErrorKind::UnsupportedType)
+}
+
+/// Returns `text` as a `CF_UNICODETEXT` representation, which is
+/// nul-terminated UTF-16.
+pub fn text_data(text: &str) -> (Format, Vec<u8>) {
+    let data = text
+        .encode_utf16()
+        .chain([0])
+        .flat_map(u16::to_le_bytes)
+        .collect();
+
+    (Format::new("CF_UNICODETEXT"), data)
+}
+
+/// Returns an HTML fragment as an `HTML Format` representation, whose
+/// header gives the byte offsets of the document and of the fragment.
+pub fn html_data(html: &str) -> (Format, Vec<u8>) {
+    const PREFIX: &str = "<html><body>\r\n<!--StartFragment-->";
+    const SUFFIX: &str = "<!--EndFragment-->\r\n</body></html>";
+
+    let header = |start_html, end_html, start_fragment, end_fragment| {
+        format!(
+            "Version:0.9\r\n\
+            StartHTML:{:010}\r\n\
+            EndHTML:{:010}\r\n\
+            StartFragment:{:010}\r\n\
+            EndFragment:{:010}\r\n",
+            start_html, end_html, start_fragment, end_fragment
+        )
+    };
+
+    // The offsets are padded, so the header has the same length whatever
+    // their values.
+    let start_html = header(0, 0, 0, 0).len();
+    let start_fragment = start_html + PREFIX.len();
+    let end_fragment = start_fragment + html.len();
+    let end_html = end_fragment + SUFFIX.len();
+
+    let data = header(start_html, end_html, start_fragment, end_fragment)
+        + PREFIX
+        + html
+        + SUFFIX;
+
+    (Format::new("HTML Format"), data.into_bytes())
+}
+
+/// Returns the representations that mark written contents as transient or
+/// concealed.
+///
+/// Transient contents are kept out of the clipboard history and the cloud
+/// clipboard. Concealed contents are also hidden from clipboard monitors.
+pub fn write_markers(options: &WriteOptions) -> Vec<(Format, Vec<u8>)> {
+    let mut markers = Vec::new();
+
+    if options.transient || options.concealed {
+        // A `DWORD` of 0 opts out.
+        markers.push((Format::new("CanIncludeInClipboardHistory"), vec![0; 4]));
+        markers.push((Format::new("CanUploadToCloudClipboard"), vec![0; 4]));
+    }
+
+    if options.concealed {
+        markers.push((
+            Format::new("ExcludeClipboardContentFromMonitorProcessing"),
+            vec![0; 4],
+        ));
+    }
+
+    markers
+}
+
+/// Writes each pair of format and data, replacing the contents of the
+/// clipboard.
+fn write_data(items: &[(Format, Vec<u8>)]) -> Result<(), Box<dyn Error>> {
+    let _clipboard = open()?;
+
+    raw::empty()?;
+
+    for (format, data) in items {
+        let id = format_id(format.name())
+            .ok_or_else(|| Unregistered(format.name().to_owned()))?;
+
+        raw::set_without_clear(id, data)?;
+    }
+
+    Ok(())
+}
+
+/// Writes text, and empties the clipboard once `ttl` has elapsed unless its
+/// sequence number has changed since.
+fn write_with_ttl(
+    contents: &str,
+    ttl: Duration,
+) -> Result<ClearTimer, Box<dyn Error>> {
+    check_reentrancy()?;
+    set_clipboard_string(contents)?;
+
+    let number = raw::seq_num();
+
+    Ok(ClearTimer::spawn(ttl, move || {
+        if number.is_some() && raw::seq_num() == number {
+            let _ = clear();
+        }
+    }))
+}
+
+/// Empties the clipboard.
+fn clear() -> Result<(), Box<dyn Error>> {
+    let _clipboard = open()?;
+
+    Ok(raw::empty()?)
+}
+
+/// The hidden window of the Windows backend and the thread pumping its
+/// messages.
+///
+/// Formats written with
+/// [`write_promised`](crate::Clipboard::write_promised) are declared with
+/// no data, and rendered by a message-only window when a paste sends it
+/// `WM_RENDERFORMAT`.
+///
+/// By default, the window is created on a managed thread of its own the
+/// first time it is needed. Applications that already pump messages can
+/// [`attach`](pump::attach) it to their own thread instead. Either way,
+/// [`shutdown`](pump::shutdown) destroys it.
+pub mod pump {
+    use super::{format_id, Unregistered};
+    use crate::{Format, Promise};
+
+    use clipboard_win::{raw, ErrorCode};
+
+    use std::cell::Cell;
+    use std::error::Error;
+    use std::ffi::c_void;
+    use std::io;
+    use std::panic::{self, AssertUnwindSafe};
+    use std::ptr;
+    use std::sync::{mpsc, Arc, Mutex, MutexGuard, PoisonError};
+    use std::thread;
+
+    type Hwnd = *mut c_void;
+
+    const WM_DESTROY: u32 = 0x0002;
+    const WM_CLOSE: u32 = 0x0010;
+    const WM_RENDERFORMAT: u32 = 0x0305;
+    const WM_RENDERALLFORMATS: u32 = 0x0306;
+    const WM_DESTROYCLIPBOARD: u32 = 0x0307;
+
+    /// The parent of message-only windows.
+    const HWND_MESSAGE: isize = -3;
+
+    #[repr(C)]
+    struct WndClassW {
+        style: u32,
+        wnd_proc: unsafe extern "system" fn(Hwnd, u32, usize, isize) -> isize,
+        cls_extra: i32,
+        wnd_extra: i32,
+        instance: *mut c_void,
+        icon: *mut c_void,
+        cursor: *mut c_void,
+        background: *mut c_void,
+        menu_name: *const u16,
+        class_name: *const u16,
+    }
+
+    #[repr(C)]
+    struct Msg {
+        hwnd: Hwnd,
+        message: u32,
+        wparam: usize,
+        lparam: isize,
+        time: u32,
+        point: [i32; 2],
+    }
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn RegisterClassW(class: *const WndClassW) -> u16;
+        #[allow(clippy::too_many_arguments)]
+        fn CreateWindowExW(
+            ex_style: u32,
+            class_name: *const u16,
+            window_name: *const u16,
+            style: u32,
+            x: i32,
+            y: i32,
+            width: i32,
+            height: i32,
+            parent: Hwnd,
+            menu: *mut c_void,
+            instance: *mut c_void,
+            param: *mut c_void,
+        ) -> Hwnd;
+        fn DefWindowProcW(
+            hwnd: Hwnd,
+            message: u32,
+            wparam: usize,
+            lparam: isize,
+        ) -> isize;
+        fn GetMessageW(msg: *mut Msg, hwnd: Hwnd, min: u32, max: u32) -> i32;
+        fn DispatchMessageW(msg: *const Msg) -> isize;
+        fn PostMessageW(
+            hwnd: Hwnd,
+            message: u32,
+            wparam: usize,
+            lparam: isize,
+        ) -> i32;
+        fn PostQuitMessage(exit_code: i32);
+        fn SetClipboardData(format: u32, data: *mut c_void) -> *mut c_void;
+        fn GetClipboardOwner() -> Hwnd;
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetModuleHandleW(name: *const u16) -> *mut c_void;
+    }
+
+    /// The message-only window, and the managed thread pumping its
+    /// messages, if any.
+    struct Window {
+        handle: usize,
+        thread: Option<thread::JoinHandle<()>>,
+    }
+
+    static WINDOW: Mutex<Option<Window>> = Mutex::new(None);
+
+    /// The promises of the formats declared by the last promised write.
+    static PROMISES: Mutex<Vec<(u32, Promise)>> = Mutex::new(Vec::new());
+
+    thread_local! {
+        /// Whether the current thread is the managed one.
+        static IS_MANAGED: Cell<bool> = const { Cell::new(false) };
+    }
+
+    /// Creates the hidden window on the calling thread, instead of on a
+    /// managed thread.
+    ///
+    /// The calling thread must keep dispatching its messages, like the UI
+    /// thread of any application does, for as long as promised formats may
+    /// be pasted. Fails if the window exists already.
+    pub fn attach() -> Result<(), io::Error> {
+        let mut window = self::window();
+
+        if window.is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "the clipboard window exists already",
+            ));
+        }
+
+        let handle = unsafe { create_window() };
+
+        if handle.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+
+        *window = Some(Window {
+            handle: handle as usize,
+            thread: None,
+        });
+
+        Ok(())
+    }
+
+    /// Destroys the hidden window, rendering any promised format still on
+    /// the clipboard first, and stops its managed thread, if any.
+    ///
+    /// A window [attached](attach) to another thread is destroyed once that
+    /// thread dispatches its messages. The next promised write creates a
+    /// new window on a managed thread.
+    pub fn shutdown() {
+        let Some(window) = self::window().take() else {
+            return;
+        };
+
+        let _ = unsafe { PostMessageW(window.handle as Hwnd, WM_CLOSE, 0, 0) };
+
+        if let Some(thread) = window.thread {
+            let _ = thread.join();
+        }
+    }
+
+    /// Writes the given formats and data, and declares the promised formats
+    /// for delayed rendering.
+    pub(super) fn write_promised(
+        items: &[(Format, Vec<u8>)],
+        promises: &[(Format, Promise)],
+    ) -> Result<(), Box<dyn Error>> {
+        super::check_reentrancy()?;
+
+        let owner = owner().ok_or_else(ErrorCode::last_system)?;
+
+        let promises = promises
+            .iter()
+            .map(|(format, promise)| {
+                let id = format_id(format.name())
+                    .ok_or_else(|| Unregistered(format.name().to_owned()))?;
+
+                Ok((id, Arc::clone(promise)))
+            })
+            .collect::<Result<Vec<_>, Unregistered>>()?;
+
+        let _clipboard =
+            clipboard_win::Clipboard::new_attempts_for(owner as Hwnd, 10)?;
+
+        // Emptying the clipboard sends `WM_DESTROYCLIPBOARD` to the previous
+        // owner, which drops the previous promises.
+        raw::empty()?;
+
+        for (format, data) in items {
+            let id = format_id(format.name())
+                .ok_or_else(|| Unregistered(format.name().to_owned()))?;
+
+            raw::set_without_clear(id, data)?;
+        }
+
+        for (id, _) in &promises {
+            // No data declares the format for delayed rendering.
+            let _ = unsafe { SetClipboardData(*id, ptr::null_mut()) };
+        }
+
+        *self::promises() = promises;
+
+        Ok(())
+    }
+
+    /// Returns the hidden window, creating it on a managed thread if there
+    /// is none.
+    fn owner() -> Option<usize> {
+        let mut window = self::window();
+
+        if let Some(window) = &*window {
+            return Some(window.handle);
+        }
+
+        let (sender, receiver) = mpsc::channel();
+
+        let thread = thread::Builder::new()
+            .name("window_clipboard pump".to_owned())
+            .spawn(move || {
+                IS_MANAGED.with(|is_managed| is_managed.set(true));
+
+                let handle = unsafe { create_window() };
+
+                if handle.is_null() {
+                    let _ = sender.send(None);
+                } else {
+                    let _ = sender.send(Some(handle as usize));
+                    run();
+                }
+            })
+            .ok()?;
+
+        let handle = receiver.recv().ok().flatten()?;
+
+        *window = Some(Window {
+            handle,
+            thread: Some(thread),
+        });
+
+        Some(handle)
+    }
+
+    unsafe fn create_window() -> Hwnd {
+        let class_name: Vec<u16> =
+            "window_clipboard pump\0".encode_utf16().collect();
+        let instance = GetModuleHandleW(ptr::null());
+
+        let class = WndClassW {
+            style: 0,
+            wnd_proc: window_proc,
+            cls_extra: 0,
+            wnd_extra: 0,
+            instance,
+            icon: ptr::null_mut(),
+            cursor: ptr::null_mut(),
+            background: ptr::null_mut(),
+            menu_name: ptr::null(),
+            class_name: class_name.as_ptr(),
+        };
+
+        // The class stays registered after a shutdown, so registering it
+        // again fails, which is fine.
+        let _ = RegisterClassW(&class);
+
+        CreateWindowExW(
+            0,
+            class_name.as_ptr(),
+            ptr::null(),
+            0,
+            0,
+            0,
+            0,
+            0,
+            HWND_MESSAGE as Hwnd,
+            ptr::null_mut(),
+            instance,
+            ptr::null_mut(),
+        )
+    }
+
+    /// Dispatches the messages of the managed thread until it quits.
+    fn run() {
+        let mut msg = Msg {
+            hwnd: ptr::null_mut(),
+            message: 0,
+            wparam: 0,
+            lparam: 0,
+            time: 0,
+            point: [0; 2],
+        };
+
+        while unsafe { GetMessageW(&mut msg, ptr::null_mut(), 0, 0) } > 0 {
+            let _ = unsafe { DispatchMessageW(&msg) };
+        }
+    }
+
+    unsafe extern "system" fn window_proc(
+        window: Hwnd,
+        message: u32,
+        wparam: usize,
+        lparam: isize,
+    ) -> isize {
+        match message {
+            WM_RENDERFORMAT => {
+                // The pasting application has the clipboard open already.
+                render(wparam as u32);
+                0
+            }
+            WM_RENDERALLFORMATS => {
+                let clipboard = clipboard_win::Clipboard::new_for(window);
+
+                if clipboard.is_ok() && GetClipboardOwner() == window {
+                    let ids: Vec<u32> =
+                        promises().iter().map(|(id, _)| *id).collect();
+
+                    for id in ids {
+                        render(id);
+                    }
+                }
+
+                0
+            }
+            WM_DESTROYCLIPBOARD => {
+                promises().clear();
+                0
+            }
+            WM_DESTROY => {
+                // Only the managed thread is ours to quit.
+                if IS_MANAGED.with(Cell::get) {
+                    PostQuitMessage(0);
+                }
+
+                0
+            }
+            _ => DefWindowProcW(window, message, wparam, lparam),
+        }
+    }
+
+    /// Renders a promised format into the open clipboard.
+    fn render(id: u32) {
+        let promise = promises()
+            .iter()
+            .find(|(candidate, _)| *candidate == id)
+            .map(|(_, promise)| Arc::clone(promise));
+
+        super::RENDERING.with(|rendering| rendering.set(true));
+        // A panic must not unwind into the window procedure.
+        let data = promise.and_then(|promise| {
+            panic::catch_unwind(AssertUnwindSafe(|| promise())).ok()?
+        });
+        super::RENDERING.with(|rendering| rendering.set(false));
+
+        if let Some(data) = data {
+            let _ = raw::set_without_clear(id, &data);
+        }
+    }
+
+    fn window() -> MutexGuard<'static, Option<Window>> {
+        WINDOW.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn promises() -> MutexGuard<'static, Vec<(u32, Promise)>> {
+        PROMISES.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn advertised_names_round_trip() {
+        let ids = PREDEFINED
+            .iter()
+            .map(|&(_, id)| id)
+            .chain(formats::CF_PRIVATEFIRST..=formats::CF_PRIVATELAST)
+            .chain(formats::CF_GDIOBJFIRST..=formats::CF_GDIOBJLAST);
+
+        for id in ids {
+            let name = raw::format_name_big(id).unwrap();
+
+            assert_eq!(format_id(&name), Some(id), "{name}");
+        }
+    }
 }
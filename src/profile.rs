@@ -0,0 +1,157 @@
+//! Format preference profiles.
+use crate::formats::{self, Format};
+
+use std::borrow::Cow;
+
+/// Which formats an application prefers to read, and which it refuses.
+///
+/// Formats are matched by their platform-native name, their normalized
+/// name (see [`formats::normalize`]), or a `type/*` wildcard of it. Names
+/// unknown to [`formats::normalize`] that are MIME types, like
+/// `image/webp`, match wildcards as well.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Profile {
+    preferred: Vec<Cow<'static, str>>,
+    denied: Vec<Cow<'static, str>>,
+}
+
+impl Profile {
+    /// Creates an empty [`Profile`], which accepts any format in the order
+    /// the platform lists them.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Prefers plain text, then rich text, and refuses images.
+    pub fn text_editor() -> Self {
+        Self::new()
+            .prefer("text/plain")
+            .prefer("text/html")
+            .prefer("text/rtf")
+            .deny("image/*")
+    }
+
+    /// Prefers lossless images, then any other image.
+    pub fn image_tool() -> Self {
+        Self::new()
+            .prefer("image/png")
+            .prefer("image/tiff")
+            .prefer("image/bmp")
+            .prefer("image/*")
+    }
+
+    /// Prefers file lists, then plain text.
+    pub fn file_manager() -> Self {
+        Self::new().prefer("text/uri-list").prefer("text/plain")
+    }
+
+    /// Adds a preferred format, ranked after the ones added before.
+    pub fn prefer(mut self, format: impl Into<Cow<'static, str>>) -> Self {
+        self.preferred.push(format.into());
+        self
+    }
+
+    /// Refuses a format, even if it is preferred.
+    pub fn deny(mut self, format: impl Into<Cow<'static, str>>) -> Self {
+        self.denied.push(format.into());
+        self
+    }
+
+    /// Returns the acceptable [`Format`]s among `available`, from the most
+    /// to the least preferred.
+    ///
+    /// Formats that match no preference are ranked last, in their original
+    /// order.
+    pub fn rank(&self, available: &[Format]) -> Vec<Format> {
+        let mut ranked: Vec<(usize, &Format)> = available
+            .iter()
+            .filter(|format| {
+                !self.denied.iter().any(|pattern| matches(pattern, format))
+            })
+            .map(|format| {
+                let rank = self
+                    .preferred
+                    .iter()
+                    .position(|pattern| matches(pattern, format))
+                    .unwrap_or(self.preferred.len());
+
+                (rank, format)
+            })
+            .collect();
+
+        ranked.sort_by_key(|&(rank, _)| rank);
+        ranked
+            .into_iter()
+            .map(|(_, format)| format.clone())
+            .collect()
+    }
+}
+
+fn matches(pattern: &str, format: &Format) -> bool {
+    let normalized = formats::mime_type(format.name());
+
+    if pattern == format.name() || Some(pattern) == normalized {
+        return true;
+    }
+
+    match (pattern.strip_suffix("/*"), normalized) {
+        (Some(type_), Some(normalized)) => normalized
+            .split_once('/')
+            .is_some_and(|(normalized, _)| normalized == type_),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn formats(names: &[&'static str]) -> Vec<Format> {
+        names.iter().map(|&name| Format::new(name)).collect()
+    }
+
+    #[test]
+    fn ranks_preferred_formats_first() {
+        let ranked = Profile::text_editor().rank(&formats(&[
+            "com.example.custom",
+            "public.html",
+            "public.utf8-plain-text",
+        ]));
+
+        assert_eq!(
+            ranked,
+            formats(&[
+                "public.utf8-plain-text",
+                "public.html",
+                "com.example.custom",
+            ])
+        );
+    }
+
+    #[test]
+    fn text_editor_denies_every_image() {
+        let ranked = Profile::text_editor().rank(&formats(&[
+            "public.jpeg",
+            "com.compuserve.gif",
+            "image/gif",
+            "JFIF",
+            "image/webp",
+            "public.png",
+            "CF_DIB",
+            "public.utf8-plain-text",
+        ]));
+
+        assert_eq!(ranked, formats(&["public.utf8-plain-text"]));
+    }
+
+    #[test]
+    fn wildcards_match_only_their_type() {
+        let ranked = Profile::new().deny("text/*").rank(&formats(&[
+            "UTF8_STRING",
+            "text/x-custom",
+            "image/png",
+        ]));
+
+        assert_eq!(ranked, formats(&["image/png"]));
+    }
+}
@@ -0,0 +1,104 @@
+//! Quarantine of clipboard contents written by other processes.
+//!
+//! Contents are local while the backend reports that the
+//! [`Clipboard`](crate::Clipboard) still owns the selection or, for
+//! backends that cannot tell, while the change count of the clipboard has
+//! not moved since its last write. Contents whose origin cannot be told
+//! either way are never local.
+
+/// Text read from the clipboard that must be accepted before it is used.
+#[must_use]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Untrusted {
+    text: String,
+    local: bool,
+}
+
+impl Untrusted {
+    pub(crate) fn new(text: String, local: bool) -> Self {
+        Untrusted { text, local }
+    }
+
+    /// Returns whether the text was written by the same
+    /// [`Clipboard`](crate::Clipboard), and has not been replaced since.
+    pub fn is_local(&self) -> bool {
+        self.local
+    }
+
+    /// Accepts the text, handing it to the application.
+    pub fn accept(self) -> String {
+        self.text
+    }
+}
+
+/// The error of a read refused because its contents were written by
+/// another process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("contents written by another process are quarantined")]
+pub struct Quarantined;
+
+/// What a [`Clipboard`](crate::Clipboard) knows of its last write.
+#[derive(Debug, Default)]
+pub(crate) struct Written {
+    /// The change count of the clipboard right after the write, if the
+    /// backend can track changes.
+    pub clipboard: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::internal::Board;
+    use crate::{Clipboard, ErrorKind, Format};
+
+    fn quarantined(board: &Board) -> Clipboard {
+        let mut clipboard = Clipboard::from(board.clone());
+        clipboard.set_quarantine(true);
+
+        clipboard
+    }
+
+    #[test]
+    fn admits_own_contents() {
+        let board = Board::new();
+        let mut clipboard = quarantined(&board);
+
+        clipboard.write("mine").unwrap();
+
+        assert_eq!(clipboard.read().unwrap(), "mine");
+        assert!(clipboard.read_untrusted().unwrap().is_local());
+    }
+
+    #[test]
+    fn quarantines_the_same_text_written_elsewhere() {
+        let board = Board::new();
+        let mut clipboard = quarantined(&board);
+        let mut other = Clipboard::from(board.clone());
+
+        clipboard.write("same").unwrap();
+        other.write("same").unwrap();
+
+        let error = clipboard.read().unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::Refused);
+        assert!(!clipboard.read_untrusted().unwrap().is_local());
+    }
+
+    #[test]
+    fn quarantines_every_read_path() {
+        let board = Board::new();
+        let clipboard = quarantined(&board);
+        let mut other = Clipboard::from(board.clone());
+
+        let _ = other.write_data(&[
+            (Format::new("text/plain"), b"text".to_vec()),
+            (Format::new("text/html"), b"<b>text</b>".to_vec()),
+        ]);
+
+        let text = Format::new("text/plain");
+
+        assert!(clipboard.read_format(&text).unwrap().is_err());
+        assert!(clipboard.read_html().unwrap().is_err());
+        assert!(!matches!(clipboard.read_all(), Some(Ok(_))));
+        assert!(clipboard.read_text_preview(2).is_err());
+    }
+}
@@ -0,0 +1,386 @@
+//! Tabular data, as exchanged with spreadsheets.
+//!
+//! Excel also copies cells as [`BIFF12`], a binary workbook. It can be read
+//! and written as raw bytes with [`Format`](crate::Format), but it is not
+//! parsed.
+
+/// The Windows clipboard format of cells as Excel's XML Spreadsheet 2003
+/// markup, as parsed by [`parse_xml_spreadsheet`].
+pub const XML_SPREADSHEET: &str = "XML Spreadsheet";
+
+/// The Windows clipboard format of cells as an Excel binary workbook.
+pub const BIFF12: &str = "Biff12";
+
+/// Parses tab-separated values, as spreadsheets copy them as plain text.
+///
+/// Cells may be quoted to contain tabs, line breaks or quotes. A cell that
+/// starts with a quote which is not closed at the end of the cell is read
+/// as literal text instead.
+pub fn parse_tsv(text: &str) -> Vec<Vec<String>> {
+    parse(text, '\t')
+}
+
+/// Parses comma-separated values, as described by RFC 4180.
+///
+/// Like with [`parse_tsv`], quotes that do not enclose a whole cell are
+/// read as literal text.
+pub fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    parse(text, ',')
+}
+
+/// Formats rows as tab-separated values.
+pub fn to_tsv(rows: &[Vec<String>]) -> String {
+    format(rows, '\t')
+}
+
+/// Formats rows as comma-separated values, as described by RFC 4180.
+pub fn to_csv(rows: &[Vec<String>]) -> String {
+    format(rows, ',')
+}
+
+/// Parses the cells of the first table of an XML Spreadsheet 2003 document,
+/// as Excel copies them.
+///
+/// Skipped rows and cells, as given by `ss:Index`, and cells covered by
+/// `ss:MergeAcross` are read as empty cells. Formatting is dropped.
+pub fn parse_xml_spreadsheet(xml: &str) -> Vec<Vec<String>> {
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let mut cell = None;
+    let mut data: Option<String> = None;
+    let mut rest = xml;
+
+    while let Some(start) = rest.find('<') {
+        if let Some(data) = &mut data {
+            data.push_str(&unescape(&rest[..start]));
+        }
+
+        let Some(end) = rest[start..].find('>') else {
+            break;
+        };
+
+        let tag = &rest[start + 1..start + end];
+        rest = &rest[start + end + 1..];
+
+        let is_closing = tag.starts_with('/');
+        let is_empty = tag.ends_with('/');
+        let tag = tag.trim_start_matches('/').trim_end_matches('/');
+        let (name, attributes) =
+            tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+        let name = name.rsplit(':').next().unwrap_or(name);
+
+        match (name, is_closing) {
+            ("Row", false) => {
+                if let Some(index) = attribute(attributes, "Index") {
+                    rows.resize(index.max(rows.len() + 1) - 1, Vec::new());
+                }
+
+                rows.push(Vec::new());
+            }
+            ("Cell", false) => {
+                let Some(row) = rows.last_mut() else {
+                    continue;
+                };
+
+                let column = attribute(attributes, "Index")
+                    .map_or(row.len(), |index| index.max(row.len() + 1) - 1);
+                let merged = attribute(attributes, "MergeAcross").unwrap_or(0);
+
+                row.resize(column + 1 + merged, String::new());
+                cell = Some(column);
+            }
+            ("Cell", true) => cell = None,
+            ("Data", false) if !is_empty => data = Some(String::new()),
+            ("Data", true) => {
+                let text = data.take().unwrap_or_default();
+
+                if let (Some(row), Some(column)) = (rows.last_mut(), cell) {
+                    row[column] = text;
+                }
+            }
+            ("Table", true) => break,
+            _ => {}
+        }
+    }
+
+    rows
+}
+
+/// Formats rows as an XML Spreadsheet 2003 document, with every cell as a
+/// string.
+pub fn to_xml_spreadsheet(rows: &[Vec<String>]) -> String {
+    let mut xml = String::from(
+        "<?xml version=\"1.0\"?>\r\n\
+         <?mso-application progid=\"Excel.Sheet\"?>\r\n\
+         <Workbook xmlns=\"urn:schemas-microsoft-com:office:spreadsheet\" \
+         xmlns:ss=\"urn:schemas-microsoft-com:office:spreadsheet\">\r\n\
+         <Worksheet ss:Name=\"Sheet1\">\r\n<Table>\r\n",
+    );
+
+    for row in rows {
+        xml.push_str("<Row>");
+
+        for cell in row {
+            xml.push_str("<Cell><Data ss:Type=\"String\">");
+            xml.push_str(&escape(cell));
+            xml.push_str("</Data></Cell>");
+        }
+
+        xml.push_str("</Row>\r\n");
+    }
+
+    xml.push_str("</Table>\r\n</Worksheet>\r\n</Workbook>\r\n");
+    xml
+}
+
+fn parse(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted => {
+                if chars.peek() == Some(&'"') {
+                    let _ = chars.next();
+                    cell.push('"');
+                } else {
+                    quoted = false;
+                }
+            }
+            '"' if cell.is_empty() && is_closed(chars.clone(), delimiter) => {
+                quoted = true;
+            }
+            c if quoted => cell.push(c),
+            c if c == delimiter => row.push(std::mem::take(&mut cell)),
+            '\r' | '\n' => {
+                if c == '\r' && chars.peek() == Some(&'\n') {
+                    let _ = chars.next();
+                }
+
+                row.push(std::mem::take(&mut cell));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => cell.push(c),
+        }
+    }
+
+    // The last line may have no line break.
+    if !cell.is_empty() || !row.is_empty() {
+        row.push(cell);
+        rows.push(row);
+    }
+
+    rows
+}
+
+/// Returns whether the quote opening a cell is closed right at the end of
+/// the cell, given the characters after it.
+fn is_closed(mut rest: impl Iterator<Item = char>, delimiter: char) -> bool {
+    while let Some(c) = rest.next() {
+        if c == '"' {
+            match rest.next() {
+                Some('"') => {}
+                None | Some('\r' | '\n') => return true,
+                Some(c) => return c == delimiter,
+            }
+        }
+    }
+
+    false
+}
+
+fn format(rows: &[Vec<String>], delimiter: char) -> String {
+    let mut text = String::new();
+
+    for row in rows {
+        for (i, cell) in row.iter().enumerate() {
+            if i > 0 {
+                text.push(delimiter);
+            }
+
+            if cell.contains([delimiter, '"', '\r', '\n']) {
+                text.push('"');
+                text.push_str(&cell.replace('"', "\"\""));
+                text.push('"');
+            } else {
+                text.push_str(cell);
+            }
+        }
+
+        text.push_str("\r\n");
+    }
+
+    text
+}
+
+/// Returns the numeric value of the attribute with the given local name.
+fn attribute(attributes: &str, name: &str) -> Option<usize> {
+    let mut rest = attributes;
+
+    while let Some((key, value)) = rest.split_once('=') {
+        let value = value.trim_start();
+        let quote = value.chars().next()?;
+        let (value, after) = value[1..].split_once(quote)?;
+        let key = key.trim();
+
+        if key.rsplit(':').next() == Some(name) {
+            return value.trim().parse().ok();
+        }
+
+        rest = after;
+    }
+
+    None
+}
+
+/// Resolves the entity and character references of XML text.
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let reference = rest[1..].split_once(';').and_then(|(name, _)| {
+            let c = match name {
+                "amp" => '&',
+                "lt" => '<',
+                "gt" => '>',
+                "quot" => '"',
+                "apos" => '\'',
+                _ => {
+                    let code = match name.strip_prefix("#x") {
+                        Some(hex) => u32::from_str_radix(hex, 16),
+                        None => name.strip_prefix('#')?.parse(),
+                    };
+
+                    char::from_u32(code.ok()?)?
+                }
+            };
+
+            Some((c, name.len() + 2))
+        });
+
+        match reference {
+            Some((c, length)) => {
+                unescaped.push(c);
+                rest = &rest[length..];
+            }
+            None => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    unescaped.push_str(rest);
+    unescaped
+}
+
+/// Escapes text for XML, keeping line breaks as character references.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\r' => escaped.push_str("&#13;"),
+            '\n' => escaped.push_str("&#10;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(rows: &[&[&str]]) -> Vec<Vec<String>> {
+        rows.iter()
+            .map(|row| row.iter().map(|&cell| cell.to_owned()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn parses_tab_separated_values() {
+        assert_eq!(
+            parse_tsv("a\tb\r\nc\td"),
+            rows(&[&["a", "b"], &["c", "d"]])
+        );
+        assert_eq!(parse_tsv("a\t\n"), rows(&[&["a", ""]]));
+    }
+
+    #[test]
+    fn parses_quoted_cells() {
+        assert_eq!(
+            parse_tsv("\"a\tb\"\t\"c\nd\"\n\"say \"\"hi\"\"\""),
+            rows(&[&["a\tb", "c\nd"], &["say \"hi\""]])
+        );
+        assert_eq!(parse_csv("\"a,b\",c"), rows(&[&["a,b", "c"]]));
+    }
+
+    #[test]
+    fn reads_unmatched_quotes_literally() {
+        assert_eq!(
+            parse_tsv("\"a\tb\nc\td"),
+            rows(&[&["\"a", "b"], &["c", "d"]])
+        );
+        assert_eq!(
+            parse_tsv("\"quoted\" text\tb"),
+            rows(&[&["\"quoted\" text", "b"]])
+        );
+        assert_eq!(parse_tsv("5\" disk\t\""), rows(&[&["5\" disk", "\""]]));
+    }
+
+    #[test]
+    fn round_trips_rows() {
+        let table = rows(&[&["a\tb", "c,d"], &["e\"f", "g\r\nh"], &[""]]);
+
+        assert_eq!(parse_tsv(&to_tsv(&table)), table);
+        assert_eq!(parse_csv(&to_csv(&table)), table);
+    }
+
+    #[test]
+    fn parses_xml_spreadsheets() {
+        let xml = r#"<?xml version="1.0"?>
+            <Workbook xmlns="urn:schemas-microsoft-com:office:spreadsheet"
+             xmlns:ss="urn:schemas-microsoft-com:office:spreadsheet">
+             <Worksheet ss:Name="Sheet 1">
+              <Table ss:ExpandedColumnCount="4" ss:ExpandedRowCount="3">
+               <Row>
+                <Cell><Data ss:Type="String">a &amp; b</Data></Cell>
+                <Cell ss:Index="3"><Data ss:Type="Number">1</Data></Cell>
+               </Row>
+               <Row ss:Index="3">
+                <Cell ss:MergeAcross="1">
+                 <ss:Data ss:Type="String"
+                  xmlns="http://www.w3.org/TR/REC-html40"
+                 ><B>x</B>&#10;y</ss:Data>
+                </Cell>
+                <Cell/>
+               </Row>
+              </Table>
+             </Worksheet>
+            </Workbook>"#;
+
+        assert_eq!(
+            parse_xml_spreadsheet(xml),
+            rows(&[&["a & b", "", "1"], &[], &["x\ny", "", ""]])
+        );
+    }
+
+    #[test]
+    fn round_trips_xml_spreadsheets() {
+        let table = rows(&[&["<a>", "\"b\" & 'c'"], &["d\r\ne", ""]]);
+
+        assert_eq!(parse_xml_spreadsheet(&to_xml_spreadsheet(&table)), table);
+    }
+}
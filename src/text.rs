@@ -0,0 +1,134 @@
+//! Handling of pasted text.
+use std::borrow::Cow;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The mark appended to shortened text.
+const ELLIPSIS: &str = "\u{2026}";
+
+/// Text read from the clipboard, told apart by whether it has any content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PastedText {
+    /// The clipboard holds no text at all.
+    Empty,
+    /// The text consists only of whitespace.
+    Whitespace(String),
+    /// The text has some content besides whitespace.
+    Text(String),
+}
+
+impl PastedText {
+    /// Classifies the given text.
+    pub fn new(text: String) -> Self {
+        if text.is_empty() {
+            PastedText::Empty
+        } else if text.trim().is_empty() {
+            PastedText::Whitespace(text)
+        } else {
+            PastedText::Text(text)
+        }
+    }
+
+    /// Returns the text if it has any content besides whitespace, so that
+    /// empty pastes can be treated as no-ops.
+    pub fn into_text(self) -> Option<String> {
+        match self {
+            PastedText::Text(text) => Some(text),
+            PastedText::Empty | PastedText::Whitespace(_) => None,
+        }
+    }
+}
+
+/// Shortens `text` to at most `max_bytes`, for previews and snippets.
+///
+/// The text is cut at a grapheme cluster boundary, so emoji and Hangul
+/// syllables are never split, and ends with an ellipsis if anything was
+/// cut. Text that fits is returned as is.
+pub fn preview(text: &str, max_bytes: usize) -> Cow<'_, str> {
+    if text.len() <= max_bytes {
+        return Cow::Borrowed(text);
+    }
+
+    let ellipsis = if max_bytes < ELLIPSIS.len() {
+        ""
+    } else {
+        ELLIPSIS
+    };
+    let budget = max_bytes - ellipsis.len();
+
+    let end = text
+        .grapheme_indices(true)
+        .map(|(start, grapheme)| start + grapheme.len())
+        .take_while(|&end| end <= budget)
+        .last()
+        .unwrap_or(0);
+
+    Cow::Owned(format!("{}{}", &text[..end], ellipsis))
+}
+
+/// A Unicode normalization form applied to text read from and written to
+/// the clipboard.
+#[cfg(feature = "normalization")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Normalization {
+    /// Canonical composition, as produced by most applications.
+    Nfc,
+    /// Canonical decomposition, as used by macOS file names.
+    Nfd,
+}
+
+#[cfg(feature = "normalization")]
+impl Normalization {
+    pub(crate) fn apply(self, text: &str) -> String {
+        use unicode_normalization::UnicodeNormalization;
+
+        match self {
+            Normalization::Nfc => text.nfc().collect(),
+            Normalization::Nfd => text.nfd().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_text_that_fits() {
+        assert_eq!(preview("hello", 5), Cow::Borrowed("hello"));
+        assert_eq!(preview("", 0), Cow::Borrowed(""));
+    }
+
+    #[test]
+    fn shortens_text_with_an_ellipsis() {
+        assert_eq!(preview("hello world", 8), "hello\u{2026}");
+    }
+
+    #[test]
+    fn never_splits_grapheme_clusters() {
+        // A family emoji is a single cluster of 25 bytes.
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}\u{200D}\
+                      \u{1F466}";
+
+        assert_eq!(preview(&format!("a{}b", family), 20), "a\u{2026}");
+        assert_eq!(preview("e\u{301}e\u{301}", 5), "\u{2026}");
+    }
+
+    #[test]
+    fn drops_the_ellipsis_when_it_does_not_fit() {
+        assert_eq!(preview("hello", 2), "he");
+    }
+
+    #[test]
+    fn classifies_pasted_text() {
+        assert_eq!(PastedText::new(String::new()), PastedText::Empty);
+        assert_eq!(
+            PastedText::new(" \n".to_owned()),
+            PastedText::Whitespace(" \n".to_owned())
+        );
+        assert_eq!(
+            PastedText::new(" a ".to_owned()).into_text(),
+            Some(" a ".to_owned())
+        );
+    }
+}
@@ -21,6 +21,11 @@ pub struct Clipboard {
 }
 
 impl Clipboard {
+    /// Connect to the clipboard of the given Wayland display.
+    ///
+    /// # Safety
+    /// `display` must be a valid `wl_display` pointer that outlives the
+    /// returned [`Clipboard`].
     pub unsafe fn connect(display: *mut c_void) -> Clipboard {
         let context = Arc::new(Mutex::new(smithay_clipboard::Clipboard::new(
             display as *mut _,
@@ -43,7 +48,10 @@ impl Clipboard {
         Ok(())
     }
 
-    pub fn write_primary(&mut self, data: String) -> Result<(), Box<dyn Error>> {
+    pub fn write_primary(
+        &mut self,
+        data: String,
+    ) -> Result<(), Box<dyn Error>> {
         self.context.lock().unwrap().store_primary(data);
 
         Ok(())
@@ -14,14 +14,55 @@ pub enum Error {
     ReplyError(#[from] ReplyError),
     #[error("timeout")]
     Timeout,
+    #[error("target unavailable: {0}")]
+    Unavailable(Atom),
     #[error("unexpected type: {0}")]
     UnexpectedType(Atom),
     #[error("invalid utf8 string: {0}")]
     InvalidUtf8(std::string::FromUtf8Error),
     #[error("deadlock")]
     SelectionLocked,
+    #[error("clipboard accessed from a converter")]
+    Reentrancy,
     #[error("invalid selection owner")]
     InvalidOwner,
+    #[error("missing X extension: {0}")]
+    MissingExtension(&'static str),
     #[error("worker communication error")]
     SendError(#[from] mpsc::SendError<Atom>),
 }
+
+/// An error reported by the X11 server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServerError {
+    /// The error code, as it appears on the wire.
+    pub error_code: u8,
+    /// The major opcode of the failed request.
+    pub major_opcode: u8,
+    /// The minor opcode of the failed request.
+    pub minor_opcode: u16,
+}
+
+impl Error {
+    /// Returns the [`ServerError`] that caused this [`Error`], if any.
+    pub fn server_error(&self) -> Option<ServerError> {
+        match self {
+            Error::ReplyError(ReplyError::X11Error(error)) => {
+                Some(ServerError {
+                    error_code: error.error_code,
+                    major_opcode: error.major_opcode,
+                    minor_opcode: error.minor_opcode,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    pub(crate) fn is_connection_lost(&self) -> bool {
+        matches!(
+            self,
+            Error::ConnectionErrored(_)
+                | Error::ReplyError(ReplyError::ConnectionError(_))
+        )
+    }
+}
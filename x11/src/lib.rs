@@ -1,78 +1,397 @@
 #[forbid(unsafe_code)]
 mod error;
 
-pub use error::Error;
+pub use error::{Error, ServerError};
 
-use x11rb::connection::Connection as _;
+use x11rb::connection::{Connection as _, RequestConnection as _};
 use x11rb::errors::ConnectError;
+use x11rb::protocol::xfixes::{self, ConnectionExt as _};
 use x11rb::protocol::xproto::{self, Atom, AtomEnum, EventMask, Window};
 use x11rb::protocol::Event;
 use x11rb::rust_connection::RustConnection as Connection;
 use x11rb::wrapper::ConnectionExt;
 
+use std::borrow::Cow;
+use std::cell::Cell;
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::convert::TryFrom;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock};
 use std::thread;
 use std::time::{Duration, Instant};
 
+/// Logs a format negotiation decision when the `log` feature is enabled.
+macro_rules! negotiation {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "log")]
+        log::debug!($($arg)*);
+    };
+}
+
 const POLL_DURATION: std::time::Duration = Duration::from_micros(50);
 
+/// How long a read waits for the selection owner by default.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// The representations of a selection, as pairs of target and data.
+type Representations = Vec<(Atom, Vec<u8>)>;
+
+/// The offer served for each owned selection.
+type Selections = Arc<RwLock<HashMap<Atom, Offer>>>;
+
+/// Synthesizes the data of a target from the data of another one, returning
+/// `None` if the data cannot be converted.
+pub type Converter = Arc<dyn Fn(&[u8]) -> Option<Vec<u8>> + Send + Sync>;
+
+thread_local! {
+    /// Whether the current thread is running a [`Converter`].
+    static CONVERTING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// The number of offers made so far, which identifies each of them.
+static OFFERS: AtomicU64 = AtomicU64::new(0);
+
+/// The representations of an owned selection, and the conversions that can
+/// synthesize further targets from them on request.
+struct Offer {
+    representations: Representations,
+    conversions: Vec<(Atom, Atom, Converter)>,
+    generation: u64,
+}
+
+/// A write made by [`Clipboard::write_revocable`].
+pub struct Revocable {
+    writer: Arc<Context>,
+    selections: Selections,
+    generation: u64,
+}
+
+impl Revocable {
+    /// Empties the CLIPBOARD selection unless it has changed since the
+    /// write, returning whether it did.
+    ///
+    /// The previous value is not restored.
+    pub fn revoke(self) -> Result<bool, Error> {
+        check_reentrancy()?;
+
+        clear_clipboard(&self.writer, &self.selections, Some(self.generation))
+    }
+}
+
 /// A connection to an X11 [`Clipboard`].
 pub struct Clipboard {
-    reader: Context,
+    reader: Mutex<Arc<Context>>,
     writer: Arc<Context>,
-    selections: Arc<RwLock<HashMap<Atom, (Atom, Vec<u8>)>>>,
+    selections: Selections,
+    converters: Vec<(String, String, Converter)>,
+    reconnect: bool,
+    timeout: Duration,
+    target_timeouts: HashMap<String, Duration>,
+    changes: Arc<AtomicU64>,
+    watching: bool,
 }
 
 impl Clipboard {
     /// Connect to the running X11 server and obtain a [`Clipboard`].
     pub fn connect() -> Result<Self, Error> {
-        let reader = Context::new(None)?;
+        let reader = Mutex::new(Arc::new(Context::new(None)?));
         let writer = Arc::new(Context::new(None)?);
         let selections = Arc::new(RwLock::new(HashMap::new()));
+        let changes = Arc::new(AtomicU64::new(0));
+        let watching = watch_changes(&writer);
 
-        let worker = Worker {
-            context: Arc::clone(&writer),
-            selections: Arc::clone(&selections),
-        };
-
-        thread::spawn(move || worker.run());
+        Worker::spawn(&writer, &selections, &changes);
 
         Ok(Clipboard {
             reader,
             writer,
             selections,
+            converters: Vec::new(),
+            reconnect: true,
+            timeout: DEFAULT_TIMEOUT,
+            target_timeouts: HashMap::new(),
+            changes,
+            watching,
         })
     }
 
-    fn read_selection(&self, selection: Atom) -> Result<String, Error> {
-        Ok(String::from_utf8(self.load(
+    /// Registers a [`Converter`] that synthesizes the `to` target from the
+    /// `from` target of the selections written afterwards.
+    ///
+    /// The conversion only runs when a client requests `to` and the written
+    /// contents include `from` but not `to`. Any [`Clipboard`] operation
+    /// performed by the [`Converter`] fails with [`Error::Reentrancy`].
+    pub fn register_converter(
+        &mut self,
+        from: &str,
+        to: &str,
+        converter: Converter,
+    ) {
+        self.converters
+            .push((from.to_owned(), to.to_owned(), converter));
+    }
+
+    /// Sets whether the [`Clipboard`] reconnects to the X11 server when it
+    /// finds its connection lost, retrying the failed operation once.
+    ///
+    /// This is enabled by default.
+    pub fn set_reconnect(&mut self, reconnect: bool) {
+        self.reconnect = reconnect;
+    }
+
+    /// Sets how long reads wait for the selection owner to provide the
+    /// data of a target without a timeout of its own.
+    ///
+    /// This is 3 seconds by default.
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    /// Sets how long reads wait for the selection owner to provide the
+    /// data of the given `target`, overriding the default timeout.
+    ///
+    /// Known-slow targets, like large images, may need longer than text.
+    pub fn set_target_timeout(&mut self, target: &str, timeout: Duration) {
+        let _ = self.target_timeouts.insert(target.to_owned(), timeout);
+    }
+
+    fn timeout_of(&self, target: &str) -> Duration {
+        self.target_timeouts
+            .get(target)
+            .copied()
+            .unwrap_or(self.timeout)
+    }
+
+    fn read_selection(
+        &self,
+        selection: fn(&Atoms) -> Atom,
+        limit: usize,
+    ) -> Result<String, Error> {
+        let mut bytes = match self.load_selection(
             selection,
-            self.reader.atoms.utf8_string,
-            self.reader.atoms.property,
-            std::time::Duration::from_secs(3),
-        )?)
-        .map_err(Error::InvalidUtf8)?)
+            |reader| Ok(reader.atoms.utf8_string),
+            self.timeout_of("UTF8_STRING"),
+            limit,
+        ) {
+            // Selections without text have always read as empty.
+            Err(Error::Unavailable(_)) => Vec::new(),
+            result => result?,
+        };
+
+        // A limited read may cut the last character short.
+        if let Err(error) = std::str::from_utf8(&bytes) {
+            if error.error_len().is_none() {
+                bytes.truncate(error.valid_up_to());
+            }
+        }
+
+        String::from_utf8(bytes).map_err(Error::InvalidUtf8)
+    }
+
+    fn load_selection(
+        &self,
+        selection: fn(&Atoms) -> Atom,
+        target: impl Fn(&Context) -> Result<Atom, Error>,
+        timeout: Duration,
+        limit: usize,
+    ) -> Result<Vec<u8>, Error> {
+        let result = self.with_reader(|reader| {
+            let target = target(reader)?;
+
+            negotiation!(
+                "requesting target {} of selection {}",
+                target,
+                selection(&reader.atoms)
+            );
+
+            Self::load(
+                reader,
+                selection(&reader.atoms),
+                target,
+                reader.atoms.property,
+                timeout,
+                limit,
+            )
+        });
+
+        #[cfg(feature = "log")]
+        match &result {
+            Ok(bytes) => {
+                negotiation!("received {} bytes", bytes.len());
+            }
+            Err(error) => {
+                negotiation!("request failed: {}", error);
+            }
+        }
+
+        result
+    }
+
+    fn with_reader<T>(
+        &self,
+        f: impl Fn(&Context) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        check_reentrancy()?;
+
+        let reader = Arc::clone(&self.reader());
+
+        match f(&reader) {
+            Err(error) if self.reconnect && error.is_connection_lost() => {
+                let reader = Arc::new(Context::new(None)?);
+                *self.reader() = Arc::clone(&reader);
+
+                f(&reader)
+            }
+            result => result,
+        }
+    }
+
+    fn reader(&self) -> MutexGuard<'_, Arc<Context>> {
+        self.reader.lock().unwrap_or_else(PoisonError::into_inner)
     }
 
     /// Read the current CLIPBOARD [`Clipboard`] value.
     pub fn read(&self) -> Result<String, Error> {
-        self.read_selection(self.reader.atoms.clipboard)
+        self.read_selection(|atoms| atoms.clipboard, usize::MAX)
     }
 
-
     /// Read the current PRIMARY [`Clipboard`] value.
     pub fn read_primary(&self) -> Result<String, Error> {
-        self.read_selection(self.reader.atoms.primary)
+        self.read_selection(|atoms| atoms.primary, usize::MAX)
     }
 
-    fn write_selection(&mut self, selection: Atom, contents: String) -> Result<(), Error> {
-        let target = self.writer.atoms.utf8_string;
+    /// Read at most `max_bytes` of the current CLIPBOARD [`Clipboard`]
+    /// value, cut at a character boundary.
+    ///
+    /// Incremental transfers stop once enough data has arrived, so huge
+    /// values are never loaded in full.
+    pub fn read_preview(&self, max_bytes: usize) -> Result<String, Error> {
+        self.read_selection(|atoms| atoms.clipboard, max_bytes)
+    }
+
+    /// Returns whether the CLIPBOARD [`Clipboard`] offers each of the given
+    /// `targets`, fetching its `TARGETS` once.
+    pub fn available_targets(
+        &self,
+        targets: &[&str],
+    ) -> Result<Vec<bool>, Error> {
+        let timeout = self.timeout_of("TARGETS");
+
+        let (targets, offered) = self.with_reader(|reader| {
+            let targets = intern_atoms(&reader.connection, targets)?;
+            let offered = Self::load_targets(reader, timeout)?;
+
+            Ok((targets, offered))
+        })?;
+
+        Ok(targets
+            .iter()
+            .map(|target| offered.contains(target))
+            .collect())
+    }
+
+    /// Returns the names of the targets offered by the CLIPBOARD
+    /// [`Clipboard`], leaving out the `TARGETS`, `MULTIPLE`, `TIMESTAMP`
+    /// and `SAVE_TARGETS` meta targets.
+    pub fn targets(&self) -> Result<Vec<String>, Error> {
+        let timeout = self.timeout_of("TARGETS");
+
+        let names = self.with_reader(|reader| {
+            let cookies = Self::load_targets(reader, timeout)?
+                .into_iter()
+                .map(|atom| xproto::get_atom_name(&reader.connection, atom))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            cookies
+                .into_iter()
+                .map(|cookie| {
+                    Ok(String::from_utf8_lossy(&cookie.reply()?.name)
+                        .into_owned())
+                })
+                .collect::<Result<Vec<_>, Error>>()
+        })?;
+
+        Ok(names
+            .into_iter()
+            .filter(|name| {
+                !["TARGETS", "MULTIPLE", "TIMESTAMP", "SAVE_TARGETS"]
+                    .contains(&name.as_str())
+            })
+            .collect())
+    }
+
+    /// Fetches the `TARGETS` of the CLIPBOARD [`Clipboard`].
+    fn load_targets(
+        reader: &Context,
+        timeout: Duration,
+    ) -> Result<Vec<Atom>, Error> {
+        let targets = match Self::load(
+            reader,
+            reader.atoms.clipboard,
+            reader.atoms.targets,
+            reader.atoms.property,
+            timeout,
+            usize::MAX,
+        ) {
+            Err(Error::Unavailable(_)) => Vec::new(),
+            result => result?,
+        };
+
+        Ok(targets
+            .chunks_exact(4)
+            .map(|atom| {
+                Atom::from_ne_bytes([atom[0], atom[1], atom[2], atom[3]])
+            })
+            .collect())
+    }
+
+    /// Read the data of the given `target` of the CLIPBOARD [`Clipboard`].
+    pub fn read_target(&self, target: &str) -> Result<Vec<u8>, Error> {
+        self.load_selection(
+            |atoms| atoms.clipboard,
+            |reader| get_atom(&reader.connection, target),
+            self.timeout_of(target),
+            usize::MAX,
+        )
+    }
+
+    fn write_selection(
+        &mut self,
+        selection: fn(&Atoms) -> Atom,
+        representations: impl Fn(&Context) -> Result<Representations, Error>,
+    ) -> Result<(), Error> {
+        check_reentrancy()?;
+
+        match self.own_selection(selection, &representations) {
+            Err(error) if self.reconnect && error.is_connection_lost() => {
+                self.reconnect_writer()?;
+                self.own_selection(selection, &representations)
+            }
+            result => result,
+        }
+    }
+
+    fn own_selection(
+        &self,
+        selection: fn(&Atoms) -> Atom,
+        representations: impl Fn(&Context) -> Result<Representations, Error>,
+    ) -> Result<(), Error> {
+        let selection = selection(&self.writer.atoms);
+        let representations = representations(&self.writer)?;
+        let conversions = self.conversions(&representations)?;
 
         self.selections
             .write()
             .map_err(|_| Error::SelectionLocked)?
-            .insert(selection, (target, contents.into()));
+            .insert(
+                selection,
+                Offer {
+                    representations,
+                    conversions,
+                    generation: OFFERS.fetch_add(1, Ordering::Relaxed),
+                },
+            );
 
         let _ = xproto::set_selection_owner(
             &self.writer.connection,
@@ -81,7 +400,7 @@ impl Clipboard {
             x11rb::CURRENT_TIME,
         )?;
 
-        let _ = self.writer.connection.flush()?;
+        self.writer.connection.flush()?;
 
         let reply =
             xproto::get_selection_owner(&self.writer.connection, selection)
@@ -95,32 +414,204 @@ impl Clipboard {
         }
     }
 
+    /// Resolves the registered converters that apply to `representations`.
+    fn conversions(
+        &self,
+        representations: &Representations,
+    ) -> Result<Vec<(Atom, Atom, Converter)>, Error> {
+        let mut conversions = Vec::new();
+
+        for (from, to, converter) in &self.converters {
+            let from = get_atom(&self.writer.connection, from)?;
+            let to = get_atom(&self.writer.connection, to)?;
+            let offered = |atom| {
+                representations.iter().any(|&(target, _)| target == atom)
+            };
+
+            if offered(from)
+                && !offered(to)
+                && conversions.iter().all(|&(_, target, _)| target != to)
+            {
+                conversions.push((from, to, Arc::clone(converter)));
+            }
+        }
+
+        Ok(conversions)
+    }
+
+    fn reconnect_writer(&mut self) -> Result<(), Error> {
+        self.writer = Arc::new(Context::new(None)?);
+
+        // The selections were owned on the lost connection, and their atoms
+        // may name something else on the new one.
+        self.selections
+            .write()
+            .map_err(|_| Error::SelectionLocked)?
+            .clear();
+
+        self.watching = watch_changes(&self.writer);
+
+        // Changes may have been missed while disconnected.
+        let _ = self.changes.fetch_add(1, Ordering::Relaxed);
+
+        Worker::spawn(&self.writer, &self.selections, &self.changes);
+
+        Ok(())
+    }
+
     /// Write a new value to the CLIPBOARD [`Clipboard`].
     pub fn write(&mut self, contents: String) -> Result<(), Error> {
-        let selection = self.writer.atoms.clipboard;
-        self.write_selection(selection, contents)
+        self.write_selection(
+            |atoms| atoms.clipboard,
+            |writer| {
+                Ok(vec![(writer.atoms.utf8_string, contents.clone().into())])
+            },
+        )
     }
 
     /// Write a new value to the PRIMARY [`Clipboard`].
     pub fn write_primary(&mut self, contents: String) -> Result<(), Error> {
-        let selection = self.writer.atoms.primary;
-        self.write_selection(selection, contents)
+        self.write_selection(
+            |atoms| atoms.primary,
+            |writer| {
+                Ok(vec![(writer.atoms.utf8_string, contents.clone().into())])
+            },
+        )
+    }
+
+    /// Write a new value to the CLIPBOARD [`Clipboard`], offering it only
+    /// as the given `targets`.
+    ///
+    /// `STRING` targets are encoded as Latin-1, replacing any character
+    /// outside of it with `?`. Any other target is encoded as UTF-8.
+    pub fn write_text_types(
+        &mut self,
+        contents: String,
+        targets: &[&str],
+    ) -> Result<(), Error> {
+        self.write_selection(
+            |atoms| atoms.clipboard,
+            |writer| {
+                targets
+                    .iter()
+                    .map(|&name| {
+                        let target = get_atom(&writer.connection, name)?;
+
+                        let data = if target == writer.atoms.string {
+                            contents
+                                .chars()
+                                .map(|c| u8::try_from(c).unwrap_or(b'?'))
+                                .collect()
+                        } else {
+                            contents.as_bytes().to_vec()
+                        };
+
+                        Ok((target, data))
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    /// Write each pair of target name and data to the clipboard selection,
+    /// offering only those targets.
+    pub fn write_data(&mut self, items: &[(&str, &[u8])]) -> Result<(), Error> {
+        self.write_selection(
+            |atoms| atoms.clipboard,
+            |writer| {
+                items
+                    .iter()
+                    .map(|&(name, data)| {
+                        Ok((get_atom(&writer.connection, name)?, data.to_vec()))
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    /// Returns the number of times the owner of the CLIPBOARD [`Clipboard`]
+    /// has been set since the connection was made.
+    ///
+    /// Every copy sets the owner, so the count changes whenever the
+    /// contents may have. It requires the XFIXES extension.
+    pub fn change_count(&self) -> Result<u64, Error> {
+        if self.watching {
+            Ok(self.changes.load(Ordering::Relaxed))
+        } else {
+            Err(Error::MissingExtension(xfixes::X11_EXTENSION_NAME))
+        }
+    }
+
+    /// Returns whether this [`Clipboard`] still owns the CLIPBOARD
+    /// selection, which holds what it wrote last until another client
+    /// copies.
+    pub fn owns_clipboard(&self) -> Result<bool, Error> {
+        self.owns(self.writer.atoms.clipboard)
+    }
+
+    /// Returns whether this [`Clipboard`] still owns the PRIMARY selection.
+    pub fn owns_primary(&self) -> Result<bool, Error> {
+        self.owns(self.writer.atoms.primary)
+    }
+
+    fn owns(&self, selection: Atom) -> Result<bool, Error> {
+        let reply =
+            xproto::get_selection_owner(&self.writer.connection, selection)
+                .map_err(Into::into)
+                .and_then(|cookie| cookie.reply())?;
+
+        Ok(reply.owner == self.writer.window)
+    }
+
+    /// Empties the CLIPBOARD [`Clipboard`], leaving the selection without
+    /// an owner, whichever client owned it.
+    pub fn clear(&mut self) -> Result<(), Error> {
+        check_reentrancy()?;
+
+        let _ = clear_clipboard(&self.writer, &self.selections, None)?;
+
+        Ok(())
+    }
+
+    /// Write a new value to the CLIPBOARD [`Clipboard`], returning a
+    /// [`Revocable`] that can empty it later unless it has changed since.
+    pub fn write_revocable(
+        &mut self,
+        contents: String,
+    ) -> Result<Revocable, Error> {
+        self.write(contents)?;
+
+        // This handle is the only one writing its selections.
+        let generation = self
+            .selections
+            .read()
+            .map_err(|_| Error::SelectionLocked)?
+            .get(&self.writer.atoms.clipboard)
+            .map(|offer| offer.generation)
+            .ok_or(Error::InvalidOwner)?;
+
+        Ok(Revocable {
+            writer: Arc::clone(&self.writer),
+            selections: Arc::clone(&self.selections),
+            generation,
+        })
     }
 
     /// load value.
     fn load(
-        &self,
+        reader: &Context,
         selection: Atom,
         target: Atom,
         property: Atom,
         timeout: impl Into<Option<Duration>>,
+        limit: usize,
     ) -> Result<Vec<u8>, Error> {
         let mut buff = Vec::new();
         let timeout = timeout.into();
 
         let _ = xproto::convert_selection(
-            &self.reader.connection,
-            self.reader.window,
+            &reader.connection,
+            reader.window,
             selection,
             target,
             property,
@@ -128,33 +619,49 @@ impl Clipboard {
                                  // Clients should not use CurrentTime for the time argument of a ConvertSelection request.
                                  // Instead, they should use the timestamp of the event that caused the request to be made.
         )?;
-        let _ = self.reader.connection.flush()?;
+        reader.connection.flush()?;
 
-        self.process_event(&mut buff, selection, target, property, timeout)?;
+        Self::process_event(
+            reader, &mut buff, selection, target, property, timeout, limit,
+        )?;
 
         let _ = xproto::delete_property(
-            &self.reader.connection,
-            self.reader.window,
+            &reader.connection,
+            reader.window,
             property,
         )?;
-        let _ = self.reader.connection.flush()?;
+        reader.connection.flush()?;
+
+        buff.truncate(limit);
 
         Ok(buff)
     }
 
     fn process_event<T>(
-        &self,
+        reader: &Context,
         buff: &mut Vec<u8>,
         selection: Atom,
         target: Atom,
         property: Atom,
         timeout: T,
+        limit: usize,
     ) -> Result<(), Error>
     where
         T: Into<Option<Duration>>,
     {
         let mut is_incr = false;
+        // Once `limit` is reached, the rest of an INCR transfer is still
+        // acknowledged, so the owner does not leave chunks behind in the
+        // property for the next read.
+        let mut is_draining = false;
         let timeout = timeout.into();
+
+        // `TARGETS` is answered with a list of atoms.
+        let type_ = if target == reader.atoms.targets {
+            AtomEnum::ATOM.into()
+        } else {
+            target
+        };
         let start_time = if timeout.is_some() {
             Some(Instant::now())
         } else {
@@ -169,10 +676,21 @@ impl Clipboard {
                 .map(|(timeout, time)| (Instant::now() - time) >= timeout)
                 .unwrap_or(false)
             {
+                if is_draining {
+                    let _ = xproto::delete_property(
+                        &reader.connection,
+                        reader.window,
+                        property,
+                    );
+                    let _ = reader.connection.flush();
+
+                    return Ok(());
+                }
+
                 return Err(Error::Timeout);
             }
 
-            let event = match self.reader.connection.poll_for_event()? {
+            let event = match reader.connection.poll_for_event()? {
                 Some(event) => event,
                 None => {
                     thread::park_timeout(POLL_DURATION);
@@ -189,37 +707,38 @@ impl Clipboard {
                     // Note that setting the property argument to None indicates that the
                     // conversion requested could not be made.
                     if event.property == AtomEnum::NONE.into() {
-                        break;
+                        return Err(Error::Unavailable(target));
                     }
 
                     let reply = xproto::get_property(
-                        &self.reader.connection,
+                        &reader.connection,
                         false,
-                        self.reader.window,
+                        reader.window,
                         event.property,
                         Atom::from(AtomEnum::ANY),
                         buff.len() as u32,
-                        ::std::u32::MAX, // FIXME reasonable buffer size
+                        // The length is given in 32-bit units.
+                        u32::try_from(limit.div_ceil(4)).unwrap_or(u32::MAX),
                     )
                     .map_err(Into::into)
                     .and_then(|cookie| cookie.reply())?;
 
-                    if reply.type_ == self.reader.atoms.incr {
-                        if let Some(&size) = reply.value.get(0) {
+                    if reply.type_ == reader.atoms.incr {
+                        if let Some(&size) = reply.value.first() {
                             buff.reserve(size as usize);
                         }
 
                         let _ = xproto::delete_property(
-                            &self.reader.connection,
-                            self.reader.window,
+                            &reader.connection,
+                            reader.window,
                             property,
                         );
 
-                        let _ = self.reader.connection.flush();
+                        let _ = reader.connection.flush();
                         is_incr = true;
 
                         continue;
-                    } else if reply.type_ != target {
+                    } else if reply.type_ != type_ {
                         return Err(Error::UnexpectedType(reply.type_));
                     }
 
@@ -232,9 +751,9 @@ impl Clipboard {
                     };
 
                     let length = xproto::get_property(
-                        &self.reader.connection,
+                        &reader.connection,
                         false,
-                        self.reader.window,
+                        reader.window,
                         property,
                         Atom::from(AtomEnum::ANY),
                         0,
@@ -244,10 +763,26 @@ impl Clipboard {
                     .and_then(|cookie| cookie.reply())?
                     .bytes_after;
 
+                    if is_draining {
+                        let _ = xproto::delete_property(
+                            &reader.connection,
+                            reader.window,
+                            property,
+                        );
+                        let _ = reader.connection.flush();
+
+                        // A zero-length chunk ends the transfer.
+                        if length == 0 {
+                            break;
+                        }
+
+                        continue;
+                    }
+
                     let reply = xproto::get_property(
-                        &self.reader.connection,
+                        &reader.connection,
                         true,
-                        self.reader.window,
+                        reader.window,
                         property,
                         Atom::from(AtomEnum::ANY),
                         0,
@@ -256,12 +791,16 @@ impl Clipboard {
                     .map_err(Into::into)
                     .and_then(|cookie| cookie.reply())?;
 
-                    if reply.type_ != target {
+                    if reply.type_ != type_ {
                         continue;
                     };
 
                     if reply.value_len != 0 {
                         buff.extend_from_slice(&reply.value);
+
+                        if buff.len() >= limit {
+                            is_draining = true;
+                        }
                     } else {
                         break;
                     }
@@ -292,6 +831,100 @@ pub struct Atoms {
     pub incr: Atom,
 }
 
+/// Drops the offer of the CLIPBOARD selection and leaves it without owner.
+///
+/// With a `generation`, it only does so while that offer still owns the
+/// selection, returning whether it did.
+fn clear_clipboard(
+    context: &Context,
+    selections: &Selections,
+    generation: Option<u64>,
+) -> Result<bool, Error> {
+    let selection = context.atoms.clipboard;
+
+    // The offer outlives the ownership until the worker handles the
+    // `SelectionClear`, so the owner is asked as well.
+    let is_owner = match generation {
+        Some(_) => {
+            xproto::get_selection_owner(&context.connection, selection)
+                .map_err(Into::into)
+                .and_then(|cookie| cookie.reply())?
+                .owner
+                == context.window
+        }
+        None => true,
+    };
+
+    let mut selections =
+        selections.write().map_err(|_| Error::SelectionLocked)?;
+
+    let is_offered = match generation {
+        Some(generation) => matches!(
+            selections.get(&selection),
+            Some(offer) if offer.generation == generation
+        ),
+        None => true,
+    };
+
+    if !is_owner || !is_offered {
+        return Ok(false);
+    }
+
+    let _ = selections.remove(&selection);
+
+    let _ = xproto::set_selection_owner(
+        &context.connection,
+        x11rb::NONE,
+        selection,
+        x11rb::CURRENT_TIME,
+    )?;
+
+    context.connection.flush()?;
+
+    Ok(true)
+}
+
+/// Asks the server to notify the worker of `context` whenever the owner of
+/// the CLIPBOARD selection changes, returning whether it will.
+fn watch_changes(context: &Context) -> bool {
+    let watch = || -> Result<(), Error> {
+        let _ = context.connection.xfixes_query_version(5, 0)?.reply()?;
+
+        context
+            .connection
+            .xfixes_select_selection_input(
+                context.window,
+                context.atoms.clipboard,
+                xfixes::SelectionEventMask::SET_SELECTION_OWNER
+                    | xfixes::SelectionEventMask::SELECTION_WINDOW_DESTROY
+                    | xfixes::SelectionEventMask::SELECTION_CLIENT_CLOSE,
+            )?
+            .check()?;
+
+        context.connection.flush()?;
+
+        Ok(())
+    };
+
+    watch().is_ok()
+}
+
+/// Interns all the given atom `names` in a single round-trip.
+fn intern_atoms(
+    connection: &Connection,
+    names: &[&str],
+) -> Result<Vec<Atom>, Error> {
+    let cookies = names
+        .iter()
+        .map(|name| xproto::intern_atom(connection, false, name.as_bytes()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    cookies
+        .into_iter()
+        .map(|cookie| Ok(cookie.reply()?.atom))
+        .collect()
+}
+
 #[inline]
 fn get_atom(connection: &Connection, name: &str) -> Result<Atom, Error> {
     x11rb::protocol::xproto::intern_atom(connection, false, name.as_bytes())
@@ -310,7 +943,7 @@ impl Context {
 
         {
             let screen =
-                connection.setup().roots.get(screen as usize).ok_or(
+                connection.setup().roots.get(screen).ok_or(
                     Error::ConnectionFailed(ConnectError::InvalidScreen),
                 )?;
 
@@ -332,7 +965,7 @@ impl Context {
                 ),
             )?;
 
-            let _ = connection.flush()?;
+            connection.flush()?;
         }
 
         let atoms = Atoms {
@@ -356,13 +989,92 @@ impl Context {
 
 pub struct Worker {
     context: Arc<Context>,
-    selections: Arc<RwLock<HashMap<Atom, (Atom, Vec<u8>)>>>,
+    selections: Selections,
+    changes: Arc<AtomicU64>,
+}
+
+/// An outgoing value too large for a single request, sent in chunks with
+/// the `INCR` protocol.
+struct Transfer {
+    target: Atom,
+    data: Vec<u8>,
+    offset: usize,
+}
+
+impl Offer {
+    /// Returns the data of `target`, converting it from another
+    /// representation if needed.
+    fn data(&self, target: Atom) -> Option<Cow<'_, [u8]>> {
+        if let Some((_, data)) = self
+            .representations
+            .iter()
+            .find(|&&(atom, _)| atom == target)
+        {
+            return Some(Cow::Borrowed(data));
+        }
+
+        let (from, _, converter) = self
+            .conversions
+            .iter()
+            .find(|&&(_, atom, _)| atom == target)?;
+
+        let (_, data) = self
+            .representations
+            .iter()
+            .find(|&&(atom, _)| atom == *from)?;
+
+        negotiation!("converting target {} to {}", from, target);
+
+        CONVERTING.with(|converting| converting.set(true));
+        // A panic must not kill the worker, which still owns the selection,
+        // so the request is refused instead.
+        let data = panic::catch_unwind(AssertUnwindSafe(|| converter(data)));
+        CONVERTING.with(|converting| converting.set(false));
+
+        data.ok().flatten().map(Cow::Owned)
+    }
+}
+
+/// Fails if called from a [`Converter`].
+///
+/// Converters run on the worker thread while it holds the selections, so
+/// any [`Clipboard`] operation they perform would deadlock or time out.
+fn check_reentrancy() -> Result<(), Error> {
+    if CONVERTING.with(Cell::get) {
+        Err(Error::Reentrancy)
+    } else {
+        Ok(())
+    }
 }
 
 impl Worker {
-    pub const INCR_CHUNK_SIZE: usize = 4000;
+    pub const INCR_CHUNK_SIZE: usize = 64 * 1024;
+
+    fn spawn(
+        context: &Arc<Context>,
+        selections: &Selections,
+        changes: &Arc<AtomicU64>,
+    ) {
+        let worker = Worker {
+            context: Arc::clone(context),
+            selections: Arc::clone(selections),
+            changes: Arc::clone(changes),
+        };
+
+        thread::spawn(move || worker.run());
+    }
 
     pub fn run(self) {
+        // Leave room for the header of a `ChangeProperty` request.
+        let max_value = self
+            .context
+            .connection
+            .maximum_request_bytes()
+            .saturating_sub(64);
+        let chunk_size = Self::INCR_CHUNK_SIZE.min(max_value);
+
+        let mut transfers: HashMap<(Window, Atom), Transfer> = HashMap::new();
+
         while let Ok(event) = self.context.connection.wait_for_event() {
             match event {
                 Event::SelectionRequest(event) => {
@@ -371,14 +1083,37 @@ impl Worker {
                         None => continue,
                     };
 
-                    let &(target, ref value) =
-                        match selections.get(&event.selection) {
-                            Some(key_value) => key_value,
-                            None => continue,
-                        };
+                    let offer = match selections.get(&event.selection) {
+                        Some(offer) => offer,
+                        None => continue,
+                    };
+
+                    // Answering with a `None` property refuses the request.
+                    let mut property = event.property;
+
+                    negotiation!(
+                        "requestor {} asked for target {} of selection {}",
+                        event.requestor,
+                        event.target,
+                        event.selection
+                    );
 
                     if event.target == self.context.atoms.targets {
-                        let data = [self.context.atoms.targets, target];
+                        let data: Vec<Atom> =
+                            std::iter::once(self.context.atoms.targets)
+                                .chain(
+                                    offer
+                                        .representations
+                                        .iter()
+                                        .map(|&(target, _)| target),
+                                )
+                                .chain(
+                                    offer
+                                        .conversions
+                                        .iter()
+                                        .map(|&(_, target, _)| target),
+                                )
+                                .collect();
 
                         self.context
                             .connection
@@ -390,18 +1125,45 @@ impl Worker {
                                 &data,
                             )
                             .expect("Change property");
-                    } else {
-                        let _ = self
-                            .context
-                            .connection
-                            .change_property8(
-                                xproto::PropMode::REPLACE,
+                    } else if let Some(value) = offer.data(event.target) {
+                        if value.len() > max_value {
+                            negotiation!(
+                                "sending {} bytes to requestor {} with INCR",
+                                value.len(),
+                                event.requestor
+                            );
+
+                            self.start_transfer(
                                 event.requestor,
                                 event.property,
-                                target,
-                                value,
-                            )
-                            .expect("Change property");
+                                value.len(),
+                            );
+
+                            let _ = transfers.insert(
+                                (event.requestor, event.property),
+                                Transfer {
+                                    target: event.target,
+                                    data: value.into_owned(),
+                                    offset: 0,
+                                },
+                            );
+                        } else {
+                            let _ = self
+                                .context
+                                .connection
+                                .change_property8(
+                                    xproto::PropMode::REPLACE,
+                                    event.requestor,
+                                    event.property,
+                                    event.target,
+                                    &value,
+                                )
+                                .expect("Change property");
+                        }
+                    } else {
+                        negotiation!("target {} is not offered", event.target);
+
+                        property = AtomEnum::NONE.into();
                     }
 
                     let _ = xproto::send_event(
@@ -416,13 +1178,33 @@ impl Worker {
                             requestor: event.requestor,
                             selection: event.selection,
                             target: event.target,
-                            property: event.property,
+                            property,
                         },
                     )
                     .expect("Send event");
 
                     let _ = self.context.connection.flush();
                 }
+                Event::PropertyNotify(event)
+                    if event.state == xproto::Property::DELETE =>
+                {
+                    let key = (event.window, event.atom);
+
+                    if let Some(transfer) = transfers.get_mut(&key) {
+                        if self.send_chunk(&key, transfer, chunk_size) {
+                            let _ = transfers.remove(&key);
+                        }
+                    }
+                }
+                Event::DestroyNotify(event) => {
+                    // A requestor that went away never deletes the
+                    // property again, so its transfers would never end.
+                    transfers
+                        .retain(|&(requestor, _), _| requestor != event.window);
+                }
+                Event::XfixesSelectionNotify(_) => {
+                    let _ = self.changes.fetch_add(1, Ordering::Relaxed);
+                }
                 Event::SelectionClear(event) => {
                     if let Ok(mut write_setmap) = self.selections.write() {
                         write_setmap.remove(&event.selection);
@@ -432,4 +1214,67 @@ impl Worker {
             }
         }
     }
+
+    /// Announces an `INCR` transfer of `length` bytes on the `property` of
+    /// the `requestor`, watching it for deletions and for its destruction.
+    fn start_transfer(&self, requestor: Window, property: Atom, length: usize) {
+        let connection = &self.context.connection;
+
+        let _ = xproto::change_window_attributes(
+            connection,
+            requestor,
+            &xproto::ChangeWindowAttributesAux::new().event_mask(
+                EventMask::PROPERTY_CHANGE | EventMask::STRUCTURE_NOTIFY,
+            ),
+        );
+
+        let _ = connection.change_property32(
+            xproto::PropMode::REPLACE,
+            requestor,
+            property,
+            self.context.atoms.incr,
+            &[u32::try_from(length).unwrap_or(u32::MAX)],
+        );
+    }
+
+    /// Sends the next chunk of an `INCR` transfer, once the requestor has
+    /// deleted the previous one.
+    ///
+    /// Returns whether the transfer is complete, which is signaled with an
+    /// empty chunk.
+    fn send_chunk(
+        &self,
+        &(requestor, property): &(Window, Atom),
+        transfer: &mut Transfer,
+        chunk_size: usize,
+    ) -> bool {
+        let connection = &self.context.connection;
+
+        let end = transfer.data.len().min(transfer.offset + chunk_size);
+        let chunk = &transfer.data[transfer.offset..end];
+
+        let _ = connection.change_property8(
+            xproto::PropMode::REPLACE,
+            requestor,
+            property,
+            transfer.target,
+            chunk,
+        );
+
+        let complete = chunk.is_empty();
+        transfer.offset = end;
+
+        if complete {
+            let _ = xproto::change_window_attributes(
+                connection,
+                requestor,
+                &xproto::ChangeWindowAttributesAux::new()
+                    .event_mask(EventMask::NO_EVENT),
+            );
+        }
+
+        let _ = connection.flush();
+
+        complete
+    }
 }